@@ -2,9 +2,8 @@ use std::{ffi::OsString, fs, path::PathBuf};
 
 use lishp::{
 	errors::LexerError,
-	executor::context::Context,
+	executor::{context::Context, BUILTINS},
 	lexer::{lex, Token},
-	KEYWORDS,
 };
 use reedline::{Completer, Span, Suggestion};
 
@@ -15,7 +14,8 @@ pub struct LishpCompleter {
 
 impl LishpCompleter {
 	pub fn new(context: Context, mut executables: Vec<String>) -> Self {
-		executables.extend(KEYWORDS.into_iter().map(String::from));
+		executables.extend(BUILTINS.iter().map(|(name, _)| name.to_string()));
+		executables.extend(context.functions.keys().cloned());
 		executables.sort_unstable();
 		Self {
 			context,
@@ -43,21 +43,20 @@ impl LishpCompleter {
 						start: pos,
 						end: pos,
 					};
-					return self.suggest_path(span);
+					self.suggest_path(span)
 				} else {
 					// If there is no space after the end of the function, add one.
 					let span = Span {
 						start: pos,
 						end: pos,
 					};
-					return self
-						.suggest_path(span)
+					self.suggest_path(span)
 						.into_iter()
 						.map(|mut s| {
 							s.value = format!(" {}", s.value);
 							s
 						})
-						.collect();
+						.collect()
 				}
 			}
 			Some(Token::String(string)) => {
@@ -79,7 +78,9 @@ impl LishpCompleter {
 						// Complete this string as a command
 						generate_suggestions_from(string, &self.commands, span)
 					}
-					Some(Token::FunctionEnd | Token::String(_) | Token::Variable(_)) => {
+					Some(
+						Token::FunctionEnd | Token::String(_) | Token::Variable(_) | Token::Spread,
+					) => {
 						// Complete this string as a path
 						let span = Span {
 							start: pos - string.len(),
@@ -94,9 +95,11 @@ impl LishpCompleter {
 					start: pos - var.len(),
 					end: pos,
 				};
-				let options = self.context.vars.keys().cloned().collect();
+				let options: Vec<String> = self.context.vars.names();
 				generate_suggestions_from(var, &options, span)
 			}
+			// A spread is always followed by what it spreads.
+			Some(Token::Spread) => vec![],
 			None => {
 				let span = Span { start: 0, end: pos };
 				generate_suggestions(&self.commands, span)
@@ -115,7 +118,7 @@ impl LishpCompleter {
 		let mut incomplete_dir = String::new();
 		if !incomplete_path.is_empty() {
 			let mut incomplete_dir_pathbuf = PathBuf::from(incomplete_path);
-			if incomplete_path.chars().last() != Some('/') {
+			if !incomplete_path.ends_with('/') {
 				// If the last char isn't a `/` then we want to pop whatever is after the
 				// last `/` off so we just have the directory.
 				incomplete_dir_pathbuf.pop();
@@ -140,7 +143,7 @@ impl LishpCompleter {
 		};
 		let files: Vec<String> = entries
 			.flatten()
-			.map(|entry| -> Result<String, OsString> {
+			.flat_map(|entry| -> Result<String, OsString> {
 				let name = entry.file_name().into_string()?;
 				Ok(if entry.path().is_dir() {
 					format!("{incomplete_dir}{name}/")
@@ -148,11 +151,10 @@ impl LishpCompleter {
 					format!("{incomplete_dir}{name} ")
 				})
 			})
-			.flatten()
 			.collect();
 		files
 			.iter()
-			.filter(|path| path.starts_with(&incomplete_path))
+			.filter(|path| path.starts_with(incomplete_path))
 			.map(|path| Suggestion {
 				value: path.to_string(),
 				description: None,
@@ -204,11 +206,11 @@ fn to_backslash_suggestion(value_description_pair: (String, String), pos: usize)
 	}
 }
 
-fn generate_suggestions(options: &Vec<String>, span: Span) -> Vec<Suggestion> {
+fn generate_suggestions(options: &[String], span: Span) -> Vec<Suggestion> {
 	generate_suggestions_from("", options, span)
 }
 
-fn generate_suggestions_from(start: &str, options: &Vec<String>, span: Span) -> Vec<Suggestion> {
+fn generate_suggestions_from(start: &str, options: &[String], span: Span) -> Vec<Suggestion> {
 	let to_suggestion = |option: &str| {
 		Suggestion {
 			value: option.to_string(),
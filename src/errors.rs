@@ -1,5 +1,7 @@
 use std::{fmt::Display, io};
 
+use crate::executor::status::Status;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum LexerError {
 	TrailingBackslash,
@@ -26,6 +28,7 @@ impl Display for LexerError {
 pub enum ParserError {
 	ExpectedFunctionNameGotEOF,
 	EndOfFunctionWhileStillTokens,
+	NothingToSpread,
 }
 
 impl Display for ParserError {
@@ -37,6 +40,9 @@ impl Display for ParserError {
 			ParserError::EndOfFunctionWhileStillTokens => {
 				"End of function but still more text after it. Hint: Do you have too many ')'?"
 			}
+			ParserError::NothingToSpread => {
+				"Expected a variable or subcommand after '...' to spread into arguments."
+			}
 		};
 		write!(f, "Parser Error: {message}")
 	}
@@ -46,6 +52,8 @@ impl Display for ParserError {
 pub struct ExecutorError {
 	error_type: ExecutorErrorType,
 	binary_name: Option<String>,
+	/// How the builtin is meant to be called, shown after the message.
+	usage: Option<String>,
 }
 
 impl ExecutorError {
@@ -53,6 +61,7 @@ impl ExecutorError {
 		Self {
 			error_type,
 			binary_name: None,
+			usage: None,
 		}
 	}
 
@@ -60,6 +69,31 @@ impl ExecutorError {
 		self.binary_name = Some(binary_name);
 		self
 	}
+
+	pub fn usage(mut self, usage: &str) -> Self {
+		self.usage = Some(usage.to_string());
+		self
+	}
+
+	pub fn error_type(&self) -> &ExecutorErrorType {
+		&self.error_type
+	}
+
+	/// The status to set `$?` to, using 127 and 126 like other shells when a command can't be
+	/// found or run.
+	pub fn status(&self) -> Status {
+		match &self.error_type {
+			ExecutorErrorType::CommandStart(e) if e.kind() == io::ErrorKind::NotFound => {
+				Status::Exited(127)
+			}
+			ExecutorErrorType::CommandStart(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+				Status::Exited(126)
+			}
+			ExecutorErrorType::FunctionFailed(error) => error.status(),
+			ExecutorErrorType::Interrupted => Status::Signalled(libc::SIGINT),
+			_ => Status::Exited(1),
+		}
+	}
 }
 
 impl Display for ExecutorError {
@@ -71,6 +105,27 @@ impl Display for ExecutorError {
 			}
 			ExecutorErrorType::BuiltinExecutionError(reason) => reason.to_string(),
 			ExecutorErrorType::VariableNotFound(name) => format!("Variable '{name}' not found."),
+			ExecutorErrorType::SubstitutionFailed(status, stderr) => {
+				let message = format!("Command substitution failed with status {}.", status.code());
+				if stderr.is_empty() {
+					message
+				} else {
+					format!("{message}\n{stderr}")
+				}
+			}
+			ExecutorErrorType::FunctionFailed(error) => error.to_string(),
+			ExecutorErrorType::UnknownFlag(flag) => format!(
+				"Unknown flag '{flag}'. Use ^{} to run the command instead of the builtin.",
+				self.binary_name.as_deref().unwrap_or_default()
+			),
+			ExecutorErrorType::Interrupted => "Interrupted.".to_string(),
+			ExecutorErrorType::Break | ExecutorErrorType::Continue => {
+				"Can only be used inside a loop.".to_string()
+			}
+		};
+		let message = match &self.usage {
+			Some(usage) => format!("{message} Usage: ({usage})"),
+			None => message,
 		};
 		match &self.binary_name {
 			Some(name) => write!(f, "{name}: {message}"),
@@ -87,11 +142,7 @@ impl From<ExecutorErrorType> for ExecutorError {
 
 impl From<io::Error> for ExecutorError {
 	fn from(value: io::Error) -> Self {
-		let error_type = ExecutorErrorType::CommandStart(value);
-		Self {
-			error_type,
-			binary_name: None,
-		}
+		ExecutorError::from_type(ExecutorErrorType::CommandStart(value))
 	}
 }
 
@@ -101,6 +152,18 @@ pub enum ExecutorErrorType {
 	IncorrectNumberOfArgsToBuiltinFunction,
 	VariableNotFound(String),
 	BuiltinExecutionError(String),
+	/// A command used as a value exited unsuccessfully, with the stderr it printed.
+	SubstitutionFailed(Status, String),
+	/// An error inside a function defined with `defn`, with the function as the binary name.
+	FunctionFailed(Box<ExecutorError>),
+	/// A flag given to a builtin that shares its name with a command, which is likely meant for it.
+	UnknownFlag(String),
+	/// Ctrl-C on its way back to the prompt, stopping every loop and block it passes.
+	Interrupted,
+	/// `break` on its way to the loop it stops, which is an error outside of a loop.
+	Break,
+	/// `continue` on its way to the loop it continues, which is an error outside of a loop.
+	Continue,
 }
 
 impl ExecutorErrorType {
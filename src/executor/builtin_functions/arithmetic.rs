@@ -0,0 +1,272 @@
+use std::{cmp::Ordering, num::IntErrorKind};
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{context::Context, evaluate_expression, value::Value, value_to_string},
+	parser::Expression,
+};
+
+/// A number to do arithmetic with. Integer math is checked, and mixing an integer with a float
+/// gives a float.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+	Int(i64),
+	Float(f64),
+}
+
+impl Number {
+	pub fn as_f64(self) -> f64 {
+		match self {
+			Number::Int(int) => int as f64,
+			Number::Float(float) => float,
+		}
+	}
+
+	pub fn compare(self, other: Number) -> Option<Ordering> {
+		match (self, other) {
+			(Number::Int(a), Number::Int(b)) => Some(a.cmp(&b)),
+			(a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+		}
+	}
+
+	/// An order that is consistent even when mixing big ints with floats, with NaN last, for
+	/// sorting.
+	pub fn total_cmp(self, other: Number) -> Ordering {
+		match (self, other) {
+			(Number::Int(a), Number::Int(b)) => a.cmp(&b),
+			(Number::Float(a), Number::Float(b)) => a.total_cmp(&b),
+			(Number::Int(a), Number::Float(b)) => int_float_cmp(a, b),
+			(Number::Float(a), Number::Int(b)) => int_float_cmp(b, a).reverse(),
+		}
+	}
+}
+
+/// Compare without converting the int to a float, which would round ints above 2^53.
+fn int_float_cmp(int: i64, float: f64) -> Ordering {
+	// i64::MAX as f64 is 2^63, the first float above every int.
+	if float.is_nan() || float >= i64::MAX as f64 {
+		return Ordering::Less;
+	}
+	if float < i64::MIN as f64 {
+		return Ordering::Greater;
+	}
+	let whole = float.trunc();
+	int.cmp(&(whole as i64))
+		.then_with(|| 0.0.partial_cmp(&(float - whole)).unwrap_or(Ordering::Equal))
+}
+
+impl From<Number> for Value {
+	fn from(value: Number) -> Self {
+		match value {
+			Number::Int(int) => Value::Int(int),
+			Number::Float(float) => Value::Float(float),
+		}
+	}
+}
+
+pub fn add(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	fold_numbers(args, context, "+", |a, b| match (a, b) {
+		(Number::Int(a), Number::Int(b)) => checked(a.checked_add(b)),
+		(a, b) => float(a.as_f64() + b.as_f64()),
+	})
+}
+
+/// `- x` negates, `- x y...` subtracts the rest from the first.
+pub fn subtract(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let subtract = |a, b| match (a, b) {
+		(Number::Int(a), Number::Int(b)) => checked(a.checked_sub(b)),
+		(a, b) => float(a.as_f64() - b.as_f64()),
+	};
+	if args.len() == 1 {
+		let number = evaluate_numbers(args, context, "-")?[0];
+		return subtract(Number::Int(0), number)
+			.map(Value::from)
+			.map_err(|message| arithmetic_error(message, "-"));
+	}
+	fold_numbers(args, context, "-", subtract)
+}
+
+pub fn multiply(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	fold_numbers(args, context, "*", |a, b| match (a, b) {
+		(Number::Int(a), Number::Int(b)) => checked(a.checked_mul(b)),
+		(a, b) => float(a.as_f64() * b.as_f64()),
+	})
+}
+
+/// Dividing integers gives an integer when it divides evenly and a float otherwise.
+pub fn divide(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() < 2 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("/".to_string())
+		);
+	}
+	fold_numbers(args, context, "/", |a, b| match (a, b) {
+		(_, b) if b.as_f64() == 0.0 => Err("Division by zero.".to_string()),
+		(Number::Int(a), Number::Int(b)) => match a.checked_rem(b) {
+			Some(0) => checked(a.checked_div(b)),
+			Some(_) => float(a as f64 / b as f64),
+			None => Err(overflow()),
+		},
+		(a, b) => float(a.as_f64() / b.as_f64()),
+	})
+}
+
+/// The remainder of dividing the first number by the second, which is never negative.
+pub fn modulo(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [a, b] = exactly_two(args, context, "mod")?;
+	let result = match (a, b) {
+		(_, b) if b.as_f64() == 0.0 => Err("Division by zero.".to_string()),
+		(Number::Int(a), Number::Int(b)) => checked(a.checked_rem_euclid(b)),
+		(a, b) => float(a.as_f64().rem_euclid(b.as_f64())),
+	};
+	result
+		.map(Value::from)
+		.map_err(|message| arithmetic_error(message, "mod"))
+}
+
+pub fn pow(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [base, exponent] = exactly_two(args, context, "pow")?;
+	let result = match (base, exponent) {
+		(Number::Int(base), Number::Int(exponent)) if exponent >= 0 => {
+			let exponent = u32::try_from(exponent).map_err(|_| overflow());
+			exponent.and_then(|exponent| checked(base.checked_pow(exponent)))
+		}
+		(base, exponent) => float(base.as_f64().powf(exponent.as_f64())),
+	};
+	result
+		.map(Value::from)
+		.map_err(|message| arithmetic_error(message, "pow"))
+}
+
+pub fn min(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	fold_numbers(args, context, "min", |a, b| match b.compare(a) {
+		Some(Ordering::Less) => Ok(b),
+		_ => Ok(a),
+	})
+}
+
+pub fn max(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	fold_numbers(args, context, "max", |a, b| match b.compare(a) {
+		Some(Ordering::Greater) => Ok(b),
+		_ => Ok(a),
+	})
+}
+
+pub fn abs(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 1 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("abs".to_string())
+		);
+	}
+	let result = match evaluate_numbers(args, context, "abs")?[0] {
+		Number::Int(int) => checked(int.checked_abs()),
+		Number::Float(float) => Ok(Number::Float(float.abs())),
+	};
+	result
+		.map(Value::from)
+		.map_err(|message| arithmetic_error(message, "abs"))
+}
+
+/// Read a number from a value. Strings, including the output of commands, are parsed, so
+/// `(+ 1 (wc -l file))` works.
+pub fn value_to_number(
+	value: Value,
+	builtin: &str,
+	context: &Context,
+) -> Result<Number, ExecutorError> {
+	match value {
+		Value::Int(int) => Ok(Number::Int(int)),
+		Value::Float(float) => Ok(Number::Float(float)),
+		Value::Status(status) => Ok(Number::Int(status.code().into())),
+		value => {
+			let string = value_to_string(value, context)?;
+			parse_number(string.trim()).map_err(|message| arithmetic_error(message, builtin))
+		}
+	}
+}
+
+pub fn parse_number(string: &str) -> Result<Number, String> {
+	match string.parse::<i64>() {
+		Ok(int) => return Ok(Number::Int(int)),
+		Err(e)
+			if matches!(
+				e.kind(),
+				IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+			) =>
+		{
+			return Err(overflow())
+		}
+		Err(_) => {}
+	}
+	match string.parse::<f64>() {
+		Ok(float) if float.is_finite() => Ok(Number::Float(float)),
+		_ => Err(format!("'{string}' is not a number.")),
+	}
+}
+
+fn evaluate_numbers(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+) -> Result<Vec<Number>, ExecutorError> {
+	args.into_iter()
+		.map(|arg| {
+			let value = evaluate_expression(arg, context)?;
+			value_to_number(value, builtin, context)
+		})
+		.collect()
+}
+
+/// Combine one or more numbers from left to right.
+fn fold_numbers(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	op: impl Fn(Number, Number) -> Result<Number, String>,
+) -> Result<Value, ExecutorError> {
+	if args.is_empty() {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary(builtin.to_string())
+		);
+	}
+	let mut numbers = evaluate_numbers(args, context, builtin)?.into_iter();
+	let first = numbers.next().unwrap_or(Number::Int(0));
+	numbers
+		.try_fold(first, op)
+		.map(Value::from)
+		.map_err(|message| arithmetic_error(message, builtin))
+}
+
+fn exactly_two(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+) -> Result<[Number; 2], ExecutorError> {
+	match evaluate_numbers(args, context, builtin)?.as_slice() {
+		[a, b] => Ok([*a, *b]),
+		_ => {
+			Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+				.binary(builtin.to_string()))
+		}
+	}
+}
+
+fn checked(result: Option<i64>) -> Result<Number, String> {
+	result.map(Number::Int).ok_or_else(overflow)
+}
+
+fn float(result: f64) -> Result<Number, String> {
+	if result.is_finite() {
+		Ok(Number::Float(result))
+	} else {
+		Err("Result is not a finite number.".to_string())
+	}
+}
+
+fn overflow() -> String {
+	"Integer overflow.".to_string()
+}
+
+fn arithmetic_error(message: String, builtin: &str) -> ExecutorError {
+	ExecutorErrorType::BuiltinExecutionError(message).binary(builtin.to_string())
+}
@@ -0,0 +1,62 @@
+use std::time::Instant;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		command_stdout,
+		context::Context,
+		evaluate_expression,
+		status::Status,
+		value::{Record, Value},
+	},
+	parser::Expression,
+};
+
+/// Run a command and return a record of its stdout, stderr, exit status and how long it took in
+/// seconds.
+pub fn capture(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 1 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("capture".to_string())
+		);
+	}
+	let pipeline = match evaluate_expression(args.remove(0), context)? {
+		Value::Command(pipeline) => pipeline,
+		value => {
+			return Err(ExecutorErrorType::BuiltinExecutionError(format!(
+				"Expected a command but got '{value}'"
+			))
+			.binary("capture".to_string()))
+		}
+	};
+	let start = Instant::now();
+	let output = pipeline.output()?;
+	let duration = start.elapsed();
+
+	let mut record = Record::new();
+	let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+	let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+	record.insert("stdout".to_string(), Value::String(stdout));
+	record.insert("stderr".to_string(), Value::String(stderr));
+	let status = Status::of_pipeline(&output.statuses, context.options.pipefail);
+	record.insert("status".to_string(), Value::Int(status.code().into()));
+	record.insert("duration".to_string(), Value::Float(duration.as_secs_f64()));
+	Ok(Value::Record(record))
+}
+
+/// Run a command and return its stdout exactly, keeping the trailing newline and whitespace that
+/// a subcommand would trim.
+pub fn raw(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 1 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("raw".to_string())
+		);
+	}
+	match evaluate_expression(args.remove(0), context)? {
+		Value::Command(pipeline) => Ok(Value::String(command_stdout(pipeline, context)?)),
+		value => Err(ExecutorErrorType::BuiltinExecutionError(format!(
+			"Expected a command but got '{value}'"
+		))
+		.binary("raw".to_string())),
+	}
+}
@@ -1,12 +1,12 @@
 use crate::{
 	errors::{ExecutorError, ExecutorErrorType},
-	executor::{context::Context, evaluate_expression_to_string, Value},
+	executor::{context::Context, evaluate_expression_to_string, status::Status, value::Value},
 	parser::Expression,
 };
 
 pub fn evaluate_cd(
 	mut arguments: Vec<Expression>,
-	context: &Context,
+	context: &mut Context,
 ) -> Result<Value, ExecutorError> {
 	let path = match arguments.len() {
 		0 => "~".to_string(),
@@ -32,5 +32,6 @@ pub fn evaluate_cd(
 				.binary("cd".to_string()),
 		);
 	}
-	Ok(Value::Cd(full_path))
+	context.working_dir = full_path;
+	Ok(Value::Status(Status::Exited(0)))
 }
@@ -0,0 +1,106 @@
+use std::cmp::Ordering;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::arithmetic::{parse_number, Number},
+		context::Context,
+		evaluate_expression,
+		value::Value,
+		value_to_string,
+	},
+	parser::Expression,
+};
+
+pub fn equal(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	compare_pairs(args, context, "=", |ordering| ordering == Ordering::Equal)
+}
+
+pub fn not_equal(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	compare_pairs(args, context, "!=", |ordering| ordering != Ordering::Equal)
+}
+
+pub fn less(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	compare_pairs(args, context, "<", |ordering| ordering == Ordering::Less)
+}
+
+pub fn less_or_equal(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	compare_pairs(args, context, "<=", |ordering| {
+		ordering != Ordering::Greater
+	})
+}
+
+pub fn greater(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	compare_pairs(args, context, ">", |ordering| ordering == Ordering::Greater)
+}
+
+pub fn greater_or_equal(
+	args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	compare_pairs(args, context, ">=", |ordering| ordering != Ordering::Less)
+}
+
+/// A value as it is compared: as a number if it is one or parses as one, otherwise as a string.
+pub struct Comparable {
+	string: String,
+	number: Option<Number>,
+}
+
+impl Comparable {
+	pub fn from_value(value: Value, context: &Context) -> Result<Self, ExecutorError> {
+		let number = match value {
+			Value::Int(int) => Some(Number::Int(int)),
+			Value::Float(float) => Some(Number::Float(float)),
+			_ => None,
+		};
+		let string = value_to_string(value, context)?;
+		let number = number.or_else(|| parse_number(string.trim()).ok());
+		Ok(Comparable { string, number })
+	}
+
+	pub fn compare(&self, other: &Comparable) -> Ordering {
+		let by_number = match (self.number, other.number) {
+			(Some(a), Some(b)) => a.compare(b),
+			_ => None,
+		};
+		by_number.unwrap_or_else(|| self.string.cmp(&other.string))
+	}
+
+	/// A total order for sorting, where numbers come before strings. `compare` can't be used as
+	/// it mixes the two, so `9 < 10 < 1a < 9`.
+	pub fn sort_order(&self, other: &Comparable) -> Ordering {
+		match (self.number, other.number) {
+			(Some(a), Some(b)) => a.total_cmp(b),
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(None, None) => self.string.cmp(&other.string),
+		}
+	}
+}
+
+/// True if every argument compares to the next one as `test` expects, so `(< 1 2 3)` checks that
+/// the numbers are increasing.
+fn compare_pairs(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	test: impl Fn(Ordering) -> bool,
+) -> Result<Value, ExecutorError> {
+	if args.len() < 2 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary(builtin.to_string())
+		);
+	}
+	let values = args
+		.into_iter()
+		.map(|arg| {
+			let value = evaluate_expression(arg, context)?;
+			Comparable::from_value(value, context)
+		})
+		.collect::<Result<Vec<Comparable>, ExecutorError>>()?;
+	let result = values
+		.windows(2)
+		.all(|pair| test(pair[0].compare(&pair[1])));
+	Ok(Value::Bool(result))
+}
@@ -0,0 +1,30 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::if_function::value_to_bool, context::Context, evaluate_expression,
+		value::Value,
+	},
+	parser::Expression,
+};
+
+/// `cond predicate body...` evaluates the body of the first predicate that is true, with the
+/// same rules as `if`. A predicate of `_` is always true, and if nothing matches the result is nil.
+pub fn cond(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.is_empty() || !args.len().is_multiple_of(2) {
+		return Err(ExecutorErrorType::BuiltinExecutionError(
+			"Expected pairs of a predicate and a body.".to_string(),
+		)
+		.binary("cond".to_string()));
+	}
+	let mut args = args.into_iter();
+	while let (Some(predicate), Some(body)) = (args.next(), args.next()) {
+		let matched = match predicate {
+			Expression::String(string) if string == "_" => true,
+			predicate => value_to_bool(evaluate_expression(predicate, context)?, "cond", context)?,
+		};
+		if matched {
+			return evaluate_expression(body, context);
+		}
+	}
+	Ok(Value::Nil)
+}
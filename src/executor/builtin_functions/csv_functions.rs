@@ -0,0 +1,169 @@
+use csv::{ReaderBuilder, WriterBuilder};
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context,
+		evaluate_expression, evaluate_expression_to_string,
+		value::{Record, Value},
+		value_to_data, value_to_string,
+	},
+	parser::Expression,
+};
+
+/// `from-csv [--no-header] [--tsv | --delimiter d] string` parses CSV into a list of records
+/// named by the header row, or into a list of lists of fields without one.
+pub fn from_csv(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let usage = "from-csv [--no-header] [--tsv | --delimiter d] string";
+	let (options, mut args) = parse_options(args, context, "from-csv", usage)?;
+	let string = evaluate_expression_to_string(args.remove(0), context)?;
+	let mut reader = ReaderBuilder::new()
+		.delimiter(options.delimiter)
+		.has_headers(options.header)
+		.from_reader(string.as_bytes());
+	let header = match options.header {
+		true => Some(reader.headers().map_err(read_error)?.clone()),
+		false => None,
+	};
+	let mut rows = vec![];
+	for row in reader.records() {
+		let row = row.map_err(read_error)?;
+		let fields = row.iter().map(|field| Value::String(field.to_string()));
+		rows.push(match &header {
+			Some(header) => {
+				let mut record = Record::new();
+				for (name, field) in header.iter().zip(fields) {
+					record.insert(name.to_string(), field);
+				}
+				Value::Record(record)
+			}
+			None => Value::List(fields.collect()),
+		});
+	}
+	Ok(Value::List(rows))
+}
+
+/// `to-csv [--no-header] [--tsv | --delimiter d] list` writes a list of records as CSV, with a
+/// header of every field name in the order they first appear. A list of lists is written as rows.
+pub fn to_csv(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let usage = "to-csv [--no-header] [--tsv | --delimiter d] list";
+	let (options, mut args) = parse_options(args, context, "to-csv", usage)?;
+	let rows = match evaluate_expression(args.remove(0), context)? {
+		Value::List(rows) => rows,
+		value => {
+			return Err(csv_error(
+				format!("Expected a list but got '{value}'."),
+				"to-csv",
+			))
+		}
+	};
+	let mut names: Vec<String> = vec![];
+	for row in &rows {
+		if let Value::Record(record) = row {
+			for (name, _) in record.iter() {
+				if !names.contains(name) {
+					names.push(name.clone());
+				}
+			}
+		}
+	}
+	let mut writer = WriterBuilder::new()
+		.delimiter(options.delimiter)
+		.flexible(true)
+		.from_writer(vec![]);
+	if options.header && !names.is_empty() {
+		writer.write_record(&names).map_err(write_error)?;
+	}
+	for row in rows {
+		let fields = match row {
+			Value::Record(record) => names
+				.iter()
+				.map(|name| record.get(name).cloned().unwrap_or(Value::Nil))
+				.collect(),
+			Value::List(fields) => fields,
+			value => vec![value],
+		};
+		let fields = fields
+			.into_iter()
+			.map(|field| field_to_string(field, context))
+			.collect::<Result<Vec<String>, ExecutorError>>()?;
+		writer.write_record(&fields).map_err(write_error)?;
+	}
+	let bytes = writer
+		.into_inner()
+		.map_err(|e| csv_error(e.to_string(), "to-csv"))?;
+	Ok(Value::String(String::from_utf8_lossy(&bytes).into()))
+}
+
+struct CsvOptions {
+	header: bool,
+	delimiter: u8,
+}
+
+/// Take the flags from the start of the arguments, leaving the one value argument.
+fn parse_options(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	usage: &str,
+) -> Result<(CsvOptions, Vec<Expression>), ExecutorError> {
+	let mut options = CsvOptions {
+		header: true,
+		delimiter: b',',
+	};
+	let mut args = args.into_iter().peekable();
+	while let Some(Expression::String(flag)) = args.peek() {
+		match flag.as_str() {
+			"--no-header" => options.header = false,
+			"--tsv" => options.delimiter = b'\t',
+			"--delimiter" => {
+				args.next();
+				let delimiter = match args.next() {
+					Some(arg) => evaluate_expression_to_string(arg, context)?,
+					None => break,
+				};
+				options.delimiter = match delimiter.as_bytes() {
+					[byte] => *byte,
+					_ => {
+						return Err(csv_error(
+							format!("The delimiter must be a single character, not '{delimiter}'."),
+							builtin,
+						))
+					}
+				};
+				continue;
+			}
+			_ => break,
+		}
+		args.next();
+	}
+	let args: Vec<Expression> = args.collect();
+	if args.len() != 1 {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary(builtin.to_string())
+			.usage(usage));
+	}
+	Ok((options, args))
+}
+
+fn field_to_string(value: Value, context: &Context) -> Result<String, ExecutorError> {
+	match value_to_data(value, context)? {
+		value @ (Value::List(_) | Value::Record(_) | Value::Lambda(_)) => Err(csv_error(
+			format!("Cannot write '{value}' as a CSV field."),
+			"to-csv",
+		)),
+		value => value_to_string(value, context),
+	}
+}
+
+fn read_error(e: csv::Error) -> ExecutorError {
+	csv_error(format!("Invalid CSV: {e}."), "from-csv")
+}
+
+fn write_error(e: csv::Error) -> ExecutorError {
+	csv_error(e.to_string(), "to-csv")
+}
+
+fn csv_error(message: String, builtin: &str) -> ExecutorError {
+	ExecutorErrorType::BuiltinExecutionError(message).binary(builtin.to_string())
+}
@@ -0,0 +1,59 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin, context::Context, evaluate_expression_to_string, function::Function,
+		status::Status, value::Value,
+	},
+	parser::Expression,
+};
+
+/// `defn name (params) body...` defines a function that is called by name like a command.
+pub fn defn(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() < 3 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("defn".to_string())
+		);
+	}
+	let name = evaluate_expression_to_string(args.remove(0), context)?;
+	if name.is_empty() || builtin(&name).is_some() {
+		return Err(defn_error(format!(
+			"Cannot define a function called '{name}'."
+		)));
+	}
+	let params = parse_params(args.remove(0), "defn")?;
+	let function = Function {
+		name: name.clone(),
+		params,
+		body: args,
+		scope: context.vars.clone(),
+	};
+	context.functions.insert(name, function);
+	Ok(Value::Status(Status::Exited(0)))
+}
+
+/// Parameters are written like a call with only names, `(a b c)`, or `()` for none.
+pub fn parse_params(expr: Expression, builtin: &str) -> Result<Vec<String>, ExecutorError> {
+	let func = match expr {
+		Expression::Function(func) => func,
+		_ => return Err(params_error(builtin)),
+	};
+	if func.is_empty() {
+		return Ok(vec![]);
+	}
+	std::iter::once(func.name)
+		.chain(func.arguments)
+		.map(|param| match param {
+			Expression::String(name) => Ok(name),
+			_ => Err(params_error(builtin)),
+		})
+		.collect()
+}
+
+fn params_error(builtin: &str) -> ExecutorError {
+	let message = "Parameters must be a list of names like (a b).".to_string();
+	ExecutorErrorType::BuiltinExecutionError(message).binary(builtin.to_string())
+}
+
+fn defn_error(message: String) -> ExecutorError {
+	ExecutorErrorType::BuiltinExecutionError(message).binary("defn".to_string())
+}
@@ -0,0 +1,41 @@
+use crate::{
+	errors::ExecutorError,
+	executor::{
+		context::Context, evaluate_expression, jobs::check_interrupt, run_value, status::Status,
+		value::Value,
+	},
+	parser::Expression,
+};
+
+/// `do expr...` evaluates its arguments in order in a new scope and returns the last value.
+pub fn do_function(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	context.vars.push();
+	let result = evaluate_block(args, context);
+	context.vars.pop();
+	result
+}
+
+/// Run every expression but the last like a line typed at the prompt, then return the value of
+/// the last one. A command that fails stops the block, and its status is the result.
+pub fn evaluate_block(
+	mut exprs: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	let last = match exprs.pop() {
+		Some(res) => res,
+		None => return Ok(Value::Nil),
+	};
+	for expr in exprs {
+		check_interrupt()?;
+		let value = evaluate_expression(expr, context)?;
+		let statuses = run_value(value, context)?;
+		let status = Status::of_pipeline(&statuses, context.options.pipefail);
+		context.last_status = status;
+		context.pipe_status = statuses;
+		if !status.success() {
+			return Ok(Value::Status(status));
+		}
+	}
+	check_interrupt()?;
+	evaluate_expression(last, context)
+}
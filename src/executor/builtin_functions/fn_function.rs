@@ -0,0 +1,26 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::defn::parse_params, context::Context, function::Function, value::Value,
+	},
+	parser::Expression,
+};
+
+/// `fn (params) body...` makes a lambda that captures the variables currently in scope.
+pub fn fn_function(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.len() < 2 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("fn".to_string())
+		);
+	}
+	let params = parse_params(args.remove(0), "fn")?;
+	Ok(Value::Lambda(Function {
+		name: "fn".to_string(),
+		params,
+		body: args,
+		scope: context.vars.clone(),
+	}))
+}
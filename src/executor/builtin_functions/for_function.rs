@@ -0,0 +1,39 @@
+use std::ops::ControlFlow;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::loop_function::run_iteration, context::Context, evaluate_expression,
+		evaluate_expression_to_string, status::Status, value::Value, value_to_list,
+	},
+	parser::Expression,
+};
+
+/// `for x in list body...` runs the body for every item of a list, or every line of a command's
+/// output, with the item bound to `x`.
+pub fn for_function(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.len() < 4 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("for".to_string())
+		);
+	}
+	let name = evaluate_expression_to_string(args.remove(0), context)?;
+	if evaluate_expression_to_string(args.remove(0), context)? != "in" {
+		return Err(ExecutorErrorType::BuiltinExecutionError(
+			"Expected 'in' after the variable name, like (for x in $list body)".to_string(),
+		)
+		.binary("for".to_string()));
+	}
+	let items = value_to_list(evaluate_expression(args.remove(0), context)?, context)?;
+	let mut last_status = Status::Exited(0);
+	for item in items {
+		match run_iteration(&args, Some((&name, item)), context)? {
+			ControlFlow::Continue(status) => last_status = status,
+			ControlFlow::Break(status) => return Ok(Value::Status(status)),
+		}
+	}
+	Ok(Value::Status(last_status))
+}
@@ -0,0 +1,239 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::arithmetic::{parse_number, value_to_number, Number},
+		context::Context,
+		evaluate_expression, get_var,
+		value::Value,
+		value_to_string,
+	},
+	parser::Expression,
+};
+
+/// `format template args...` fills in the placeholders of a template and returns the string.
+/// `{}` takes the next argument, `{1}` the argument at an index and `{name}` the variable `$name`.
+/// After a `:` comes `[[fill]align][+][0][width][.precision][type]` like Rust's `format!`, where
+/// the type is `x`, `X`, `o`, `b` or `e`. `{{` and `}}` are literal braces.
+pub fn format(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.is_empty() {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("format".to_string())
+			.usage("format template args..."));
+	}
+	let template = evaluate_expression(args.remove(0), context)?;
+	let template = value_to_string(template, context)?;
+	let args = args
+		.into_iter()
+		.map(|arg| evaluate_expression(arg, context))
+		.collect::<Result<Vec<Value>, ExecutorError>>()?;
+	let mut used = vec![false; args.len()];
+	let mut next = 0;
+	let mut output = String::new();
+	let mut chars = template.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'{' if chars.peek() == Some(&'{') => {
+				chars.next();
+				output.push('{');
+			}
+			'}' if chars.peek() == Some(&'}') => {
+				chars.next();
+				output.push('}');
+			}
+			'}' => {
+				return Err(format_error(
+					"Unmatched '}' in template, use '}}' for a brace.",
+				))
+			}
+			'{' => {
+				let mut placeholder = String::new();
+				loop {
+					match chars.next() {
+						Some('}') => break,
+						Some(c) => placeholder.push(c),
+						None => {
+							return Err(format_error(
+								"Unclosed '{' in template, use '{{' for a brace.",
+							))
+						}
+					}
+				}
+				let (arg, spec) = placeholder
+					.split_once(':')
+					.unwrap_or((placeholder.as_str(), ""));
+				let value = if arg.is_empty() || arg.bytes().all(|b| b.is_ascii_digit()) {
+					let index = match arg.parse::<usize>() {
+						Ok(index) => index,
+						Err(_) => {
+							next += 1;
+							next - 1
+						}
+					};
+					match args.get(index) {
+						Some(value) => {
+							used[index] = true;
+							value.clone()
+						}
+						None => {
+							return Err(format_error(&format!(
+								"Placeholder {{{placeholder}}} has no argument {}, there are only {}.",
+								index + 1,
+								args.len()
+							)))
+						}
+					}
+				} else {
+					get_var(arg.to_string(), context).map_err(|e| e.with("format".to_string()))?
+				};
+				let spec = Spec::parse(spec)?;
+				output.push_str(&spec.apply(value, context)?);
+			}
+			c => output.push(c),
+		}
+	}
+	if let Some(unused) = used.iter().position(|used| !used) {
+		return Err(format_error(&format!(
+			"Argument {} is not used by the template.",
+			unused + 1
+		)));
+	}
+	Ok(Value::String(output))
+}
+
+#[derive(Default)]
+struct Spec {
+	fill: Option<char>,
+	align: Option<char>,
+	sign: bool,
+	zero: bool,
+	width: usize,
+	precision: Option<usize>,
+	kind: Option<char>,
+}
+
+impl Spec {
+	fn parse(spec: &str) -> Result<Spec, ExecutorError> {
+		let mut result = Spec::default();
+		let chars: Vec<char> = spec.chars().collect();
+		let mut i = 0;
+		let is_align = |c: Option<&char>| matches!(c, Some('<' | '>' | '^'));
+		if is_align(chars.get(1)) {
+			result.fill = Some(chars[0]);
+			result.align = Some(chars[1]);
+			i = 2;
+		} else if is_align(chars.first()) {
+			result.align = Some(chars[0]);
+			i = 1;
+		}
+		if chars.get(i) == Some(&'+') {
+			result.sign = true;
+			i += 1;
+		}
+		if chars.get(i) == Some(&'0') {
+			result.zero = true;
+			i += 1;
+		}
+		let digits = |i: &mut usize| {
+			let start = *i;
+			while chars.get(*i).is_some_and(char::is_ascii_digit) {
+				*i += 1;
+			}
+			chars[start..*i].iter().collect::<String>().parse::<usize>()
+		};
+		if let Ok(width) = digits(&mut i) {
+			result.width = width;
+		}
+		if chars.get(i) == Some(&'.') {
+			i += 1;
+			match digits(&mut i) {
+				Ok(precision) => result.precision = Some(precision),
+				Err(_) => return Err(spec_error(spec, "expected a precision after '.'")),
+			}
+		}
+		if let Some(kind @ ('x' | 'X' | 'o' | 'b' | 'e')) = chars.get(i) {
+			result.kind = Some(*kind);
+			i += 1;
+		}
+		if let Some(c) = chars.get(i) {
+			return Err(spec_error(spec, &format!("unexpected '{c}'")));
+		}
+		Ok(result)
+	}
+
+	/// Format a value, as a number if the spec asks for one or the value is a number.
+	fn apply(&self, value: Value, context: &Context) -> Result<String, ExecutorError> {
+		let needs_number = self.kind.is_some() || self.sign || self.zero;
+		let number = match &value {
+			Value::Int(int) => Some(Number::Int(*int)),
+			Value::Float(float) => Some(Number::Float(*float)),
+			_ if needs_number => Some(value_to_number(value.clone(), "format", context)?),
+			Value::String(string) if self.precision.is_some() => parse_number(string.trim()).ok(),
+			_ => None,
+		};
+		let number = match number {
+			Some(number) => number,
+			None => {
+				let string = value_to_string(value, context)?;
+				let string = match self.precision {
+					Some(precision) => string.graphemes(true).take(precision).collect(),
+					None => string,
+				};
+				return Ok(self.pad(String::new(), string, '<'));
+			}
+		};
+		let negative = number.as_f64().is_sign_negative() && number.as_f64() != 0.0;
+		let digits = match (self.kind, number) {
+			(Some('x'), Number::Int(int)) => format!("{:x}", int.unsigned_abs()),
+			(Some('X'), Number::Int(int)) => format!("{:X}", int.unsigned_abs()),
+			(Some('o'), Number::Int(int)) => format!("{:o}", int.unsigned_abs()),
+			(Some('b'), Number::Int(int)) => format!("{:b}", int.unsigned_abs()),
+			(Some('x' | 'X' | 'o' | 'b'), Number::Float(float)) => {
+				return Err(format_error(&format!(
+					"Expected a whole number for '{}' but got {float}.",
+					self.kind.unwrap_or_default()
+				)))
+			}
+			(Some('e'), number) => match self.precision {
+				Some(precision) => format!("{:.*e}", precision, number.as_f64().abs()),
+				None => format!("{:e}", number.as_f64().abs()),
+			},
+			(_, number) => match (self.precision, number) {
+				(Some(precision), number) => format!("{:.*}", precision, number.as_f64().abs()),
+				(None, Number::Int(int)) => int.unsigned_abs().to_string(),
+				(None, Number::Float(float)) => float.abs().to_string(),
+			},
+		};
+		let sign = match (negative, self.sign) {
+			(true, _) => "-",
+			(false, true) => "+",
+			(false, false) => "",
+		};
+		Ok(self.pad(sign.to_string(), digits, '>'))
+	}
+
+	/// Pad to the width, with zeros between the sign and the digits if the spec asks for them.
+	fn pad(&self, sign: String, body: String, default_align: char) -> String {
+		let length = sign.graphemes(true).count() + body.graphemes(true).count();
+		let missing = self.width.saturating_sub(length);
+		if self.zero {
+			return sign + &"0".repeat(missing) + &body;
+		}
+		let fill = self.fill.unwrap_or(' ').to_string();
+		let (before, after) = match self.align.unwrap_or(default_align) {
+			'<' => (0, missing),
+			'^' => (missing / 2, missing - missing / 2),
+			_ => (missing, 0),
+		};
+		fill.repeat(before) + &sign + &body + &fill.repeat(after)
+	}
+}
+
+fn spec_error(spec: &str, problem: &str) -> ExecutorError {
+	format_error(&format!("Invalid format spec ':{spec}', {problem}."))
+}
+
+fn format_error(message: &str) -> ExecutorError {
+	ExecutorErrorType::BuiltinExecutionError(message.to_string()).binary("format".to_string())
+}
@@ -0,0 +1,32 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context, evaluate_expression, evaluate_expression_to_string, value::Value,
+	},
+	parser::Expression,
+};
+
+pub fn get(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 2 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("get".to_string())
+		);
+	}
+	let record = match evaluate_expression(args.remove(0), context)? {
+		Value::Record(record) => record,
+		value => {
+			return Err(ExecutorErrorType::BuiltinExecutionError(format!(
+				"Expected a record but got '{value}'"
+			))
+			.binary("get".to_string()))
+		}
+	};
+	let key = evaluate_expression_to_string(args.remove(0), context)?;
+	match record.get(&key) {
+		Some(value) => Ok(value.clone()),
+		None => Err(ExecutorErrorType::BuiltinExecutionError(format!(
+			"No field '{key}' in record"
+		))
+		.binary("get".to_string())),
+	}
+}
@@ -2,11 +2,11 @@ use std::env::{self, VarError};
 
 use crate::{
 	errors::{ExecutorError, ExecutorErrorType},
-	executor::{context::Context, evaluate_expression_to_string, Value},
+	executor::{context::Context, evaluate_expression_to_string, value::Value},
 	parser::Expression,
 };
 
-pub fn get_env(mut args: Vec<Expression>, context: &Context) -> Result<Value, ExecutorError> {
+pub fn get_env(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
 	if args.len() != 1 {
 		return Err(
 			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("get-env".to_string())
@@ -0,0 +1,128 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::if_function::value_to_bool, context::Context, evaluate_expression,
+		function::Function, jobs::check_interrupt, run_value, status::Status, value::Value,
+		value_to_data, value_to_list, value_to_string,
+	},
+	parser::Expression,
+};
+
+/// `map f list` returns a list of `f` called on every item.
+pub fn map(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (callable, items) = callable_and_list(args, context, "map")?;
+	let results = items
+		.into_iter()
+		.map(|item| {
+			check_interrupt()?;
+			value_to_data(callable.call_with(vec![item], context)?, context)
+		})
+		.collect::<Result<Vec<Value>, ExecutorError>>()?;
+	Ok(Value::List(results))
+}
+
+/// `filter f list` returns the items for which `f` is true.
+pub fn filter(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (callable, items) = callable_and_list(args, context, "filter")?;
+	let mut results = vec![];
+	for item in items {
+		let keep = callable.call_with(vec![item.clone()], context)?;
+		if value_to_bool(keep, "filter", context)? {
+			results.push(item);
+		}
+	}
+	Ok(Value::List(results))
+}
+
+/// `reduce f initial list` folds the list into one value, calling `f` with the value so far and
+/// the next item.
+pub fn reduce(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 3 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("reduce".to_string())
+		);
+	}
+	let callable = to_callable(
+		evaluate_expression(args.remove(0), context)?,
+		context,
+		"reduce",
+	)?;
+	let mut accumulator = value_to_data(evaluate_expression(args.remove(0), context)?, context)?;
+	for item in value_to_list(evaluate_expression(args.remove(0), context)?, context)? {
+		accumulator = value_to_data(
+			callable.call_with(vec![accumulator, item], context)?,
+			context,
+		)?;
+	}
+	Ok(accumulator)
+}
+
+/// `each f list` calls `f` on every item for its side effects, running any command it returns.
+pub fn each(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (callable, items) = callable_and_list(args, context, "each")?;
+	let mut last_status = Status::Exited(0);
+	for item in items {
+		check_interrupt()?;
+		let value = callable.call_with(vec![item], context)?;
+		let statuses = run_value(value, context)?;
+		last_status = Status::of_pipeline(&statuses, context.options.pipefail);
+	}
+	Ok(Value::Status(last_status))
+}
+
+/// `any f list` is true if `f` is true for at least one item, stopping at the first one.
+pub fn any(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (callable, items) = callable_and_list(args, context, "any")?;
+	for item in items {
+		if value_to_bool(callable.call_with(vec![item], context)?, "any", context)? {
+			return Ok(Value::Bool(true));
+		}
+	}
+	Ok(Value::Bool(false))
+}
+
+/// `all f list` is true if `f` is true for every item, stopping at the first one that isn't.
+pub fn all(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (callable, items) = callable_and_list(args, context, "all")?;
+	for item in items {
+		if !value_to_bool(callable.call_with(vec![item], context)?, "all", context)? {
+			return Ok(Value::Bool(false));
+		}
+	}
+	Ok(Value::Bool(true))
+}
+
+/// Something that can be passed where a function is expected is a lambda, or the name of a
+/// function defined with `defn`.
+fn to_callable(value: Value, context: &Context, builtin: &str) -> Result<Function, ExecutorError> {
+	if let Value::Lambda(lambda) = value {
+		return Ok(lambda);
+	}
+	let name = value_to_string(value, context)?;
+	match context.functions.get(&name) {
+		Some(function) => Ok(function.clone()),
+		None => Err(ExecutorErrorType::BuiltinExecutionError(format!(
+			"Expected a function but got '{name}'."
+		))
+		.binary(builtin.to_string())),
+	}
+}
+
+fn callable_and_list(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+) -> Result<(Function, Vec<Value>), ExecutorError> {
+	if args.len() != 2 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary(builtin.to_string())
+		);
+	}
+	let callable = to_callable(
+		evaluate_expression(args.remove(0), context)?,
+		context,
+		builtin,
+	)?;
+	let items = value_to_list(evaluate_expression(args.remove(0), context)?, context)?;
+	Ok((callable, items))
+}
@@ -1,11 +1,19 @@
 use crate::{
 	errors::{ExecutorError, ExecutorErrorType},
-	executor::{context::Context, evaluate_expression, evaluate_expression_to_string, Value},
+	executor::{
+		context::Context, evaluate_expression, run_value, status::Status, value::Value,
+		value_to_string,
+	},
 	parser::Expression,
 };
 
-pub fn evaluate_if(mut args: Vec<Expression>, context: &Context) -> Result<Value, ExecutorError> {
-	if args.len() != 3 {
+/// `if predicate then else` evaluates one of the branches. The else branch can be left out, in
+/// which case it is nil.
+pub fn evaluate_if(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.len() != 2 && args.len() != 3 {
 		return Err(ExecutorError::from_type(
 			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction,
 		)
@@ -13,17 +21,46 @@ pub fn evaluate_if(mut args: Vec<Expression>, context: &Context) -> Result<Value
 	}
 	let predicate = args.remove(0);
 	let true_expression = args.remove(0);
-	let false_expression = args.remove(0);
-	match evaluate_expression_to_string(predicate, context)?.as_str() {
-		"true" => evaluate_expression(true_expression, context),
-		"false" => evaluate_expression(false_expression, context),
-		arg => {
-			return Err(
-				ExecutorError::from_type(ExecutorErrorType::BuiltinExecutionError(format!(
-					"First argument must be true or false but was '{arg}'"
-				)))
-				.with("if".to_string()),
-			)
+	let false_expression = args.pop();
+	if value_to_bool(evaluate_expression(predicate, context)?, "if", context)? {
+		evaluate_expression(true_expression, context)
+	} else {
+		match false_expression {
+			Some(expr) => evaluate_expression(expr, context),
+			None => Ok(Value::Nil),
 		}
 	}
 }
+
+/// Read a predicate, which is either a bool, the string true or false, or nil for false. A command
+/// is run with its output going to the terminal and is true if it exits successfully, like a
+/// status.
+pub fn value_to_bool(
+	value: Value,
+	builtin: &str,
+	context: &mut Context,
+) -> Result<bool, ExecutorError> {
+	match value {
+		Value::Bool(bool) => return Ok(bool),
+		Value::Nil => return Ok(false),
+		Value::Status(status) => return Ok(status.success()),
+		Value::Command(pipeline) => {
+			let statuses = run_value(Value::Command(pipeline), context)?;
+			let status = Status::of_pipeline(&statuses, context.options.pipefail);
+			context.last_status = status;
+			context.pipe_status = statuses;
+			return Ok(status.success());
+		}
+		_ => {}
+	}
+	match value_to_string(value, context)?.as_str() {
+		"true" => Ok(true),
+		"false" => Ok(false),
+		arg => Err(
+			ExecutorError::from_type(ExecutorErrorType::BuiltinExecutionError(format!(
+				"Predicate must be true or false but was '{arg}'"
+			)))
+			.with(builtin.to_string()),
+		),
+	}
+}
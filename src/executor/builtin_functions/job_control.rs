@@ -0,0 +1,193 @@
+use std::io;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context, evaluate_args, finish_foreground_job, jobs::JobState, status::Status,
+		value::Value,
+	},
+	parser::Expression,
+};
+
+pub fn jobs(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if !args.is_empty() {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("jobs".to_string())
+		);
+	}
+	Ok(Value::String(context.jobs.report().join("\n")))
+}
+
+/// Continue a job in the foreground, by default the most recent one.
+pub fn fg(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let id = job_arg(args, context, "fg")?;
+	let mut job = match context.jobs.take(id) {
+		Some(res) => res,
+		None => return Err(no_such_job(id, "fg")),
+	};
+	eprintln!("{}", job.description);
+	let state = job.continue_in_foreground();
+	let statuses = finish_foreground_job(job, state, context);
+	Ok(Value::Status(Status::of_pipeline(
+		&statuses,
+		context.options.pipefail,
+	)))
+}
+
+/// Continue a stopped job in the background, by default the most recent one.
+pub fn bg(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let id = job_arg(args, context, "bg")?;
+	let continued = context.jobs.with_job(id, |job| {
+		job.continue_in_background();
+		format!("[{}] {} &", job.id, job.description)
+	});
+	match continued {
+		Some(res) => Ok(Value::String(res)),
+		None => Err(no_such_job(id, "bg")),
+	}
+}
+
+/// Wait for a job to finish, or every job that isn't stopped if no job is given.
+pub fn wait(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let ids = match job_arg(args, context, "wait")? {
+		Some(id) => vec![id],
+		None => context.jobs.ids(),
+	};
+	let mut last_status = Status::Exited(0);
+	for id in ids {
+		// Take the job out of the table so it isn't locked while waiting.
+		let mut job = match context.jobs.take(Some(id)) {
+			Some(res) => res,
+			None => return Err(no_such_job(Some(id), "wait")),
+		};
+		if job.stopped {
+			context.jobs.add(job);
+			continue;
+		}
+		match job.wait() {
+			JobState::Done(statuses) => {
+				last_status = Status::of_pipeline(&statuses, context.options.pipefail)
+			}
+			JobState::Stopped(status) => {
+				last_status = status;
+				context.jobs.add(job);
+			}
+		}
+	}
+	Ok(Value::Status(last_status))
+}
+
+/// Send a signal to jobs given as `%1` or processes given by their pid. The signal defaults to
+/// TERM and can be given as a number or name, like `-9` or `-KILL`.
+pub fn kill(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let args = evaluate_args(args, context)?;
+	let (signal, targets) = match args.split_first() {
+		Some((first, rest)) if first.starts_with('-') => (parse_signal(&first[1..])?, rest),
+		_ => (libc::SIGTERM, &args[..]),
+	};
+	if targets.is_empty() {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("kill".to_string())
+		);
+	}
+	for target in targets {
+		let sent = match target.strip_prefix('%') {
+			Some(_) => {
+				let id = parse_job_id(target, "kill")?;
+				let sent = context.jobs.with_job(Some(id), |job| {
+					let sent = job.signal(signal);
+					// A stopped job has to be continued to act on the signal.
+					if sent && job.stopped && signal != libc::SIGSTOP && signal != libc::SIGCONT {
+						job.continue_in_background();
+					}
+					sent
+				});
+				match sent {
+					Some(res) => res,
+					None => return Err(no_such_job(Some(id), "kill")),
+				}
+			}
+			None => {
+				let pid: libc::pid_t = match target.parse() {
+					Ok(res) => res,
+					Err(_) => {
+						return Err(ExecutorErrorType::BuiltinExecutionError(format!(
+							"'{target}' is not a pid or job id"
+						))
+						.binary("kill".to_string()))
+					}
+				};
+				unsafe { libc::kill(pid, signal) == 0 }
+			}
+		};
+		if !sent {
+			let e = io::Error::last_os_error();
+			return Err(
+				ExecutorErrorType::BuiltinExecutionError(format!("{target}: {e}"))
+					.binary("kill".to_string()),
+			);
+		}
+	}
+	Ok(Value::Status(Status::Exited(0)))
+}
+
+/// An optional job id, which is the only argument of `fg`, `bg` and `wait`.
+fn job_arg(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+) -> Result<Option<usize>, ExecutorError> {
+	let args = evaluate_args(args, context)?;
+	match args.as_slice() {
+		[] => Ok(None),
+		[id] => Ok(Some(parse_job_id(id, builtin)?)),
+		_ => {
+			Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+				.binary(builtin.to_string()))
+		}
+	}
+}
+
+fn parse_job_id(arg: &str, builtin: &str) -> Result<usize, ExecutorError> {
+	let number = arg.strip_prefix('%').unwrap_or(arg);
+	match number.parse() {
+		Ok(res) => Ok(res),
+		Err(_) => Err(ExecutorErrorType::BuiltinExecutionError(format!(
+			"'{arg}' is not a job id, expected something like %1"
+		))
+		.binary(builtin.to_string())),
+	}
+}
+
+fn parse_signal(name: &str) -> Result<libc::c_int, ExecutorError> {
+	if let Ok(number) = name.parse() {
+		return Ok(number);
+	}
+	let signal = match name.strip_prefix("SIG").unwrap_or(name) {
+		"HUP" => libc::SIGHUP,
+		"INT" => libc::SIGINT,
+		"QUIT" => libc::SIGQUIT,
+		"KILL" => libc::SIGKILL,
+		"USR1" => libc::SIGUSR1,
+		"USR2" => libc::SIGUSR2,
+		"TERM" => libc::SIGTERM,
+		"CONT" => libc::SIGCONT,
+		"STOP" => libc::SIGSTOP,
+		"TSTP" => libc::SIGTSTP,
+		_ => {
+			return Err(ExecutorErrorType::BuiltinExecutionError(format!(
+				"Unknown signal '{name}'"
+			))
+			.binary("kill".to_string()))
+		}
+	};
+	Ok(signal)
+}
+
+fn no_such_job(id: Option<usize>, builtin: &str) -> ExecutorError {
+	let message = match id {
+		Some(id) => format!("No job %{id}"),
+		None => "No current job".to_string(),
+	};
+	ExecutorErrorType::BuiltinExecutionError(message).binary(builtin.to_string())
+}
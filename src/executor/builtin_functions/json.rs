@@ -0,0 +1,115 @@
+use serde_json::{Map, Number};
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context,
+		evaluate_expression, evaluate_expression_to_string,
+		value::{Record, Value},
+		value_to_data,
+	},
+	parser::Expression,
+};
+
+/// `from-json string` parses JSON into records, lists, numbers, bools and nil.
+pub fn from_json(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 1 {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("from-json".to_string())
+			.usage("from-json string"));
+	}
+	let string = evaluate_expression_to_string(args.remove(0), context)?;
+	match serde_json::from_str(&string) {
+		Ok(json) => Ok(json_to_value(json)),
+		Err(e) => Err(json_error(format!("Invalid JSON: {e}."), "from-json")),
+	}
+}
+
+/// `to-json [--compact] value` turns a value into JSON, indented unless `--compact` is given.
+pub fn to_json(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let compact = args.first() == Some(&Expression::String("--compact".to_string()));
+	if compact {
+		args.remove(0);
+	}
+	if args.len() != 1 {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("to-json".to_string())
+			.usage("to-json [--compact] value"));
+	}
+	let value = evaluate_expression(args.remove(0), context)?;
+	let json = value_to_json(value, context)?;
+	let string = if compact {
+		serde_json::to_string(&json)
+	} else {
+		serde_json::to_string_pretty(&json)
+	};
+	string
+		.map(Value::String)
+		.map_err(|e| json_error(e.to_string(), "to-json"))
+}
+
+fn json_to_value(json: serde_json::Value) -> Value {
+	match json {
+		serde_json::Value::Null => Value::Nil,
+		serde_json::Value::Bool(bool) => Value::Bool(bool),
+		serde_json::Value::Number(number) => match number.as_i64() {
+			Some(int) => Value::Int(int),
+			None => Value::Float(number.as_f64().unwrap_or(f64::NAN)),
+		},
+		serde_json::Value::String(string) => Value::String(string),
+		serde_json::Value::Array(items) => {
+			Value::List(items.into_iter().map(json_to_value).collect())
+		}
+		serde_json::Value::Object(fields) => {
+			let mut record = Record::new();
+			for (key, value) in fields {
+				record.insert(key, json_to_value(value));
+			}
+			Value::Record(record)
+		}
+	}
+}
+
+/// Convert a value to JSON, running a command and using its output as a string.
+fn value_to_json(value: Value, context: &Context) -> Result<serde_json::Value, ExecutorError> {
+	let json = match value_to_data(value, context)? {
+		Value::String(string) => serde_json::Value::String(string),
+		Value::Int(int) => serde_json::Value::Number(int.into()),
+		Value::Float(float) => match Number::from_f64(float) {
+			Some(number) => serde_json::Value::Number(number),
+			None => {
+				return Err(json_error(
+					format!("JSON has no number {float}."),
+					"to-json",
+				))
+			}
+		},
+		Value::Bool(bool) => serde_json::Value::Bool(bool),
+		Value::Nil => serde_json::Value::Null,
+		Value::Status(status) => serde_json::Value::Number(status.code().into()),
+		Value::List(items) => serde_json::Value::Array(
+			items
+				.into_iter()
+				.map(|item| value_to_json(item, context))
+				.collect::<Result<_, _>>()?,
+		),
+		Value::Record(record) => {
+			let mut fields = Map::new();
+			for (key, value) in record.iter() {
+				fields.insert(key.clone(), value_to_json(value.clone(), context)?);
+			}
+			serde_json::Value::Object(fields)
+		}
+		Value::Lambda(_) | Value::Command(_) => {
+			return Err(json_error(
+				"Cannot convert a function to JSON.".to_string(),
+				"to-json",
+			))
+		}
+	};
+	Ok(json)
+}
+
+fn json_error(message: String, builtin: &str) -> ExecutorError {
+	ExecutorErrorType::BuiltinExecutionError(message).binary(builtin.to_string())
+}
@@ -1,10 +1,16 @@
 use crate::{
 	errors::{ExecutorError, ExecutorErrorType},
-	executor::{context::Context, evaluate_expression_to_string, Value},
+	executor::{
+		context::Context, evaluate_expression_to_data, evaluate_expression_to_string,
+		status::Status, value::Value,
+	},
 	parser::Expression,
 };
 
-pub fn let_function(mut args: Vec<Expression>, context: &Context) -> Result<Value, ExecutorError> {
+pub fn let_function(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
 	if args.len() != 2 {
 		return Err(ExecutorError::from_type(
 			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction,
@@ -12,6 +18,7 @@ pub fn let_function(mut args: Vec<Expression>, context: &Context) -> Result<Valu
 		.with("let".to_string()));
 	}
 	let name = evaluate_expression_to_string(args.remove(0), context)?;
-	let value = evaluate_expression_to_string(args.remove(0), context)?;
-	Ok(Value::Let(name, value))
+	let value = evaluate_expression_to_data(args.remove(0), context)?;
+	context.vars.insert(name, value);
+	Ok(Value::Status(Status::Exited(0)))
 }
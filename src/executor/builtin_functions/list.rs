@@ -0,0 +1,14 @@
+use crate::{
+	errors::ExecutorError,
+	executor::{context::Context, evaluate_expression_to_data, value::Value},
+	parser::Expression,
+};
+
+/// `list a b c` makes a list of its arguments.
+pub fn list(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let items = args
+		.into_iter()
+		.map(|arg| evaluate_expression_to_data(arg, context))
+		.collect::<Result<Vec<Value>, ExecutorError>>()?;
+	Ok(Value::List(items))
+}
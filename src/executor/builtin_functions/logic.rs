@@ -0,0 +1,40 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::if_function::value_to_bool, context::Context, evaluate_expression,
+		value::Value,
+	},
+	parser::Expression,
+};
+
+/// True if every argument is, evaluating them in order and stopping at the first false one.
+pub fn and(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	for arg in args {
+		let value = evaluate_expression(arg, context)?;
+		if !value_to_bool(value, "and", context)? {
+			return Ok(Value::Bool(false));
+		}
+	}
+	Ok(Value::Bool(true))
+}
+
+/// True if any argument is, evaluating them in order and stopping at the first true one.
+pub fn or(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	for arg in args {
+		let value = evaluate_expression(arg, context)?;
+		if value_to_bool(value, "or", context)? {
+			return Ok(Value::Bool(true));
+		}
+	}
+	Ok(Value::Bool(false))
+}
+
+pub fn not(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 1 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("not".to_string())
+		);
+	}
+	let value = evaluate_expression(args.remove(0), context)?;
+	Ok(Value::Bool(!value_to_bool(value, "not", context)?))
+}
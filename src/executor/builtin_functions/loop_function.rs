@@ -0,0 +1,75 @@
+use std::ops::ControlFlow;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::do_function::evaluate_block, context::Context, jobs::check_interrupt,
+		run_value, status::Status, value::Value,
+	},
+	parser::Expression,
+};
+
+/// `loop body...` runs the body until it uses `break`.
+pub fn loop_function(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.is_empty() {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("loop".to_string())
+		);
+	}
+	loop {
+		if let ControlFlow::Break(status) = run_iteration(&args, None, context)? {
+			return Ok(Value::Status(status));
+		}
+	}
+}
+
+pub fn break_function(args: Vec<Expression>, _: &mut Context) -> Result<Value, ExecutorError> {
+	if !args.is_empty() {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("break".to_string())
+		);
+	}
+	Err(ExecutorErrorType::Break.binary("break".to_string()))
+}
+
+pub fn continue_function(args: Vec<Expression>, _: &mut Context) -> Result<Value, ExecutorError> {
+	if !args.is_empty() {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("continue".to_string()));
+	}
+	Err(ExecutorErrorType::Continue.binary("continue".to_string()))
+}
+
+/// Run the body of a loop once in a new scope, with `binding` set if given, and run the command
+/// it returns. Breaks when the body uses `break` or a command in it is killed by Ctrl-C, with the
+/// status of the last thing run, and returns an error if Ctrl-C was pressed while the shell ran.
+pub fn run_iteration(
+	body: &[Expression],
+	binding: Option<(&str, Value)>,
+	context: &mut Context,
+) -> Result<ControlFlow<Status, Status>, ExecutorError> {
+	check_interrupt()?;
+	context.vars.push();
+	if let Some((name, value)) = binding {
+		context.vars.insert(name.to_string(), value);
+	}
+	let result = evaluate_block(body.to_vec(), context).and_then(|value| run_value(value, context));
+	context.vars.pop();
+	match result {
+		Ok(statuses) => {
+			let status = Status::of_pipeline(&statuses, context.options.pipefail);
+			context.last_status = status;
+			context.pipe_status = statuses;
+			if status == Status::Signalled(libc::SIGINT) {
+				Ok(ControlFlow::Break(status))
+			} else {
+				Ok(ControlFlow::Continue(status))
+			}
+		}
+		Err(e) => match e.error_type() {
+			ExecutorErrorType::Break => Ok(ControlFlow::Break(context.last_status)),
+			ExecutorErrorType::Continue => Ok(ControlFlow::Continue(context.last_status)),
+			_ => Err(e),
+		},
+	}
+}
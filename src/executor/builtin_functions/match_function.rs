@@ -0,0 +1,77 @@
+use glob::Pattern;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::{if_function::value_to_bool, regex_functions::compile_regex},
+		context::Context,
+		evaluate_expression,
+		value::Value,
+		value_to_data, value_to_string,
+	},
+	parser::Expression,
+};
+
+/// `match value pattern body...` evaluates the body of the first pattern that matches the value,
+/// or nil if none do. A pattern is one of:
+/// - `_`, which matches anything.
+/// - `/regex/`, which matches if the regex matches part of the value.
+/// - A glob like `*.rs` or `file-?.[ch]`.
+/// - Any other string, which has to be equal to the value.
+/// - A lambda, which is called with the value and used as a predicate like in `if`.
+/// - A bool or command, used as a predicate like in `if`.
+pub fn match_function(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.len() < 3 || args.len().is_multiple_of(2) {
+		return Err(ExecutorErrorType::BuiltinExecutionError(
+			"Expected a value, then pairs of a pattern and a body.".to_string(),
+		)
+		.binary("match".to_string()));
+	}
+	// A command is run once here and every pattern is matched against its output.
+	let value = value_to_data(evaluate_expression(args.remove(0), context)?, context)?;
+	let subject = value_to_string(value.clone(), context)?;
+	let mut args = args.into_iter();
+	while let (Some(pattern), Some(body)) = (args.next(), args.next()) {
+		if matches_pattern(&value, &subject, pattern, context)? {
+			return evaluate_expression(body, context);
+		}
+	}
+	Ok(Value::Nil)
+}
+
+fn matches_pattern(
+	value: &Value,
+	subject: &str,
+	pattern: Expression,
+	context: &mut Context,
+) -> Result<bool, ExecutorError> {
+	let pattern = match evaluate_expression(pattern, context)? {
+		Value::Lambda(lambda) => {
+			let result = lambda.call_with(vec![value.clone()], context)?;
+			return value_to_bool(result, "match", context);
+		}
+		predicate @ (Value::Bool(_) | Value::Nil | Value::Command(_) | Value::Status(_)) => {
+			return value_to_bool(predicate, "match", context);
+		}
+		pattern => value_to_string(pattern, context)?,
+	};
+	if pattern == "_" {
+		return Ok(true);
+	}
+	if let Some(regex) = pattern
+		.strip_prefix('/')
+		.and_then(|rest| rest.strip_suffix('/'))
+		.filter(|regex| !regex.is_empty())
+	{
+		return Ok(compile_regex(regex, "match")?.is_match(subject));
+	}
+	if pattern.contains(['*', '?', '[']) {
+		if let Ok(glob) = Pattern::new(&pattern) {
+			return Ok(glob.matches(subject));
+		}
+	}
+	Ok(pattern == subject)
+}
@@ -1,6 +1,33 @@
+pub mod arithmetic;
+pub mod capture;
 pub mod cd;
+pub mod comparison;
+pub mod cond;
+pub mod csv_functions;
+pub mod defn;
+pub mod do_function;
+pub mod fn_function;
+pub mod for_function;
+pub mod format;
+pub mod get;
 pub mod get_env;
+pub mod higher_order;
 pub mod if_function;
+pub mod job_control;
+pub mod json;
 pub mod let_function;
+pub mod list;
+pub mod logic;
+pub mod loop_function;
+pub mod match_function;
 pub mod pipe;
+pub mod regex_functions;
 pub mod set_env;
+pub mod set_function;
+pub mod set_option;
+pub mod status;
+pub mod strings;
+pub mod table;
+pub mod toml_functions;
+pub mod while_function;
+pub mod with_input;
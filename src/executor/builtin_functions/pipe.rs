@@ -1,50 +1,83 @@
-use std::process::{Command, Stdio};
-
 use crate::{
 	errors::{ExecutorError, ExecutorErrorType},
-	executor::{context::Context, evaluate_expression, Value},
-	parser::Expression,
+	executor::{
+		builtin, context::Context, evaluate_expression, evaluate_func_with_input,
+		pipeline::Pipeline, value::Value, value_to_string,
+	},
+	parser::{Expression, Func},
 };
 
-pub fn evaluate_pipe(mut args: Vec<Expression>, context: &Context) -> Result<Value, ExecutorError> {
-	let mut prev = match evaluate_expression(args.remove(0), context)? {
-		Value::String(s) => {
-			// Probably not the best way of doing this, but it works for now.
-			let mut command = Command::new("echo");
-			command.arg(s);
-			command
-		}
-		Value::Command(command) => command,
-		Value::Cd(_) => return Err(cannot_be_used_error("cd")),
-		Value::Let(_, _) => return Err(cannot_be_used_error("let")),
-	};
-
-	for arg in args {
-		match evaluate_expression(arg, context)? {
-			Value::String(s) => {
-				return Err(ExecutorError::from_type(
-					ExecutorErrorType::BuiltinExecutionError(format!(
-						"
-Expected command but instead attempted to pipe into string '{s}'
-Hint: If you want to pipe into the \"{s}\" command, put it in paretheses like this: ({s})
-						"
-					)),
-				))
+/// Connect each argument's output to the next one's input. External commands are joined into a
+/// pipeline, while builtins get the previous output as `$in`, see `evaluate_func_with_input`.
+pub fn evaluate_pipe(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.is_empty() {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("pipe".to_string())
+		);
+	}
+	let mut value = evaluate_expression(args.remove(0), context)?;
+	for stage in args {
+		value = match (value, stage) {
+			(Value::Command(mut pipeline), Expression::Function(func))
+				if is_builtin(&func, context) =>
+			{
+				// Builtins after a command run on a thread once the pipeline starts.
+				pipeline.push_builtin(*func, context.clone());
+				Value::Command(pipeline)
+			}
+			(Value::Command(mut pipeline), stage) => {
+				pipeline.extend(expect_command(evaluate_expression(stage, context)?)?);
+				Value::Command(pipeline)
+			}
+			(value, Expression::Function(func)) if is_builtin(&func, context) => {
+				evaluate_func_with_input(*func, value, context)?
 			}
-			Value::Command(mut command) => {
-				prev.stdout(Stdio::piped());
-				let child = prev.spawn()?;
-				command.stdin(Stdio::from(child.stdout.unwrap()));
-				prev = command;
+			(value, stage) => {
+				let input = value_to_string(value, context)?;
+				let mut pipeline = expect_command(evaluate_expression(stage, context)?)?;
+				pipeline.set_input(input);
+				Value::Command(pipeline)
 			}
-			Value::Cd(_) => return Err(cannot_be_used_error("cd")),
-			Value::Let(_, _) => return Err(cannot_be_used_error("let")),
+		};
+	}
+	Ok(value)
+}
+
+/// Whether a stage runs inside the shell, as a builtin or a function defined with `defn`.
+fn is_builtin(func: &Func, context: &mut Context) -> bool {
+	match &func.name {
+		Expression::String(name) => {
+			!matches!(name.as_str(), "pipe" | "|")
+				&& (builtin(name).is_some() || context.functions.contains_key(name))
 		}
+		_ => false,
 	}
-	Ok(Value::Command(prev))
 }
 
-fn cannot_be_used_error(function: &str) -> ExecutorError {
-	let message = format!("{function} cannot be used in a pipe.");
-	ExecutorErrorType::BuiltinExecutionError(message).binary("pipe".to_string())
+fn expect_command(value: Value) -> Result<Pipeline, ExecutorError> {
+	match value {
+		Value::Command(pipeline) => Ok(pipeline),
+		Value::String(s) => Err(ExecutorError::from_type(
+			ExecutorErrorType::BuiltinExecutionError(format!(
+				"
+Expected command but instead attempted to pipe into string '{s}'
+Hint: If you want to pipe into the \"{s}\" command, put it in paretheses like this: ({s})
+				"
+			)),
+		)),
+		value @ (Value::Int(_)
+		| Value::Float(_)
+		| Value::Bool(_)
+		| Value::Nil
+		| Value::Record(_)
+		| Value::Status(_)
+		| Value::List(_)
+		| Value::Lambda(_)) => {
+			let message = format!("Expected command but instead attempted to pipe into '{value}'");
+			Err(ExecutorErrorType::BuiltinExecutionError(message).binary("pipe".to_string()))
+		}
+	}
 }
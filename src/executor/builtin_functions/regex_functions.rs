@@ -0,0 +1,116 @@
+use regex::{Captures, Regex};
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context,
+		evaluate_expression,
+		value::{Record, Value},
+		value_to_string,
+	},
+	parser::Expression,
+};
+
+/// `re-match? pattern string` is true if the pattern matches anywhere in the string.
+pub fn re_match(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (regex, [string]) = regex_args(args, context, "re-match?", "re-match? pattern string")?;
+	Ok(Value::Bool(regex.is_match(&string)))
+}
+
+/// `re-find pattern string` returns the first match, or nil if there is none. See `match_value`
+/// for what a match looks like.
+pub fn re_find(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (regex, [string]) = regex_args(args, context, "re-find", "re-find pattern string")?;
+	Ok(match regex.captures(&string) {
+		Some(captures) => match_value(&regex, &captures),
+		None => Value::Nil,
+	})
+}
+
+/// `re-find-all pattern string` returns a list of every match.
+pub fn re_find_all(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (regex, [string]) = regex_args(args, context, "re-find-all", "re-find-all pattern string")?;
+	let matches = regex
+		.captures_iter(&string)
+		.map(|captures| match_value(&regex, &captures))
+		.collect();
+	Ok(Value::List(matches))
+}
+
+/// `re-replace pattern replacement string` replaces every match. The replacement can use groups
+/// as `\$1` or `\${name}`, escaping the `$` so it isn't read as a variable.
+pub fn re_replace(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let usage = "re-replace pattern replacement string";
+	let (regex, [replacement, string]) = regex_args(args, context, "re-replace", usage)?;
+	Ok(Value::String(
+		regex
+			.replace_all(&string, replacement.as_str())
+			.into_owned(),
+	))
+}
+
+/// `re-split pattern string` splits the string wherever the pattern matches.
+pub fn re_split(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (regex, [string]) = regex_args(args, context, "re-split", "re-split pattern string")?;
+	Ok(Value::List(
+		regex
+			.split(&string)
+			.map(|part| Value::String(part.to_string()))
+			.collect(),
+	))
+}
+
+pub fn compile_regex(pattern: &str, builtin: &str) -> Result<Regex, ExecutorError> {
+	Regex::new(pattern).map_err(|e| {
+		ExecutorErrorType::BuiltinExecutionError(format!("Invalid regex '{pattern}':\n{e}"))
+			.binary(builtin.to_string())
+	})
+}
+
+/// A match is the matched string when the pattern has no groups, a record of the named groups
+/// when it has any, and otherwise a list of the groups. Groups that didn't take part in the match
+/// are nil.
+fn match_value(regex: &Regex, captures: &Captures) -> Value {
+	let group = |group: Option<regex::Match>| match group {
+		Some(group) => Value::String(group.as_str().to_string()),
+		None => Value::Nil,
+	};
+	if regex.captures_len() == 1 {
+		return group(captures.get(0));
+	}
+	let names: Vec<&str> = regex.capture_names().flatten().collect();
+	if names.is_empty() {
+		return Value::List(captures.iter().skip(1).map(group).collect());
+	}
+	let mut record = Record::new();
+	for name in names {
+		record.insert(name.to_string(), group(captures.name(name)));
+	}
+	Value::Record(record)
+}
+
+/// Evaluate the pattern and the `N` arguments after it to strings.
+fn regex_args<const N: usize>(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	usage: &str,
+) -> Result<(Regex, [String; N]), ExecutorError> {
+	if args.len() != N + 1 {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary(builtin.to_string())
+			.usage(usage));
+	}
+	let mut strings = args
+		.into_iter()
+		.map(|arg| {
+			let value = evaluate_expression(arg, context)?;
+			value_to_string(value, context)
+		})
+		.collect::<Result<Vec<String>, ExecutorError>>()?;
+	let regex = compile_regex(&strings.remove(0), builtin)?;
+	let rest = strings.try_into().map_err(|_| {
+		ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary(builtin.to_string())
+	})?;
+	Ok((regex, rest))
+}
@@ -2,11 +2,11 @@ use std::env::set_var;
 
 use crate::{
 	errors::{ExecutorError, ExecutorErrorType},
-	executor::{context::Context, evaluate_expression_to_string, Value},
+	executor::{context::Context, evaluate_expression_to_string, value::Value},
 	parser::Expression,
 };
 
-pub fn set_env(mut args: Vec<Expression>, context: &Context) -> Result<Value, ExecutorError> {
+pub fn set_env(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
 	if args.len() != 2 {
 		return Err(ExecutorError::from_type(
 			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction,
@@ -0,0 +1,27 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context, evaluate_expression_to_data, evaluate_expression_to_string,
+		status::Status, value::Value,
+	},
+	parser::Expression,
+};
+
+/// `set! name value` changes the closest existing variable, unlike `let` which always makes a new
+/// one in the innermost scope.
+pub fn set_function(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.len() != 2 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("set!".to_string())
+		);
+	}
+	let name = evaluate_expression_to_string(args.remove(0), context)?;
+	let value = evaluate_expression_to_data(args.remove(0), context)?;
+	if !context.vars.set(&name, value) {
+		return Err(ExecutorErrorType::VariableNotFound(name).binary("set!".to_string()));
+	}
+	Ok(Value::Status(Status::Exited(0)))
+}
@@ -0,0 +1,42 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::{Context, ShellOption},
+		evaluate_expression_to_string,
+		status::Status,
+		value::Value,
+	},
+	parser::Expression,
+};
+
+pub fn set_option(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.len() != 2 {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("set-option".to_string()));
+	}
+	let name = evaluate_expression_to_string(args.remove(0), context)?;
+	let option = match ShellOption::from_name(&name) {
+		Some(res) => res,
+		None => {
+			return Err(ExecutorErrorType::BuiltinExecutionError(format!(
+				"Unknown option '{name}'"
+			))
+			.binary("set-option".to_string()))
+		}
+	};
+	let value = match evaluate_expression_to_string(args.remove(0), context)?.as_str() {
+		"true" => true,
+		"false" => false,
+		arg => {
+			return Err(ExecutorErrorType::BuiltinExecutionError(format!(
+				"Second argument must be true or false but was '{arg}'"
+			))
+			.binary("set-option".to_string()))
+		}
+	};
+	context.options.set(option, value);
+	Ok(Value::Status(Status::Exited(0)))
+}
@@ -0,0 +1,28 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{context::Context, value::Value},
+	parser::Expression,
+};
+
+pub fn status(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if !args.is_empty() {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("status".to_string())
+		);
+	}
+	Ok(Value::String(context.last_status.to_string()))
+}
+
+/// The exit code of every stage of the last pipeline, separated by spaces.
+pub fn pipe_status(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if !args.is_empty() {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("pipe-status".to_string()));
+	}
+	let codes: Vec<String> = context
+		.pipe_status
+		.iter()
+		.map(|status| status.code().to_string())
+		.collect();
+	Ok(Value::String(codes.join(" ")))
+}
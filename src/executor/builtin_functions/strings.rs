@@ -0,0 +1,245 @@
+use std::ops::RangeInclusive;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::arithmetic::{value_to_number, Number},
+		context::Context,
+		evaluate_expression, reject_flags,
+		value::Value,
+		value_to_list, value_to_string,
+	},
+	parser::Expression,
+};
+
+/// `split [separator] string` splits on the separator, or on whitespace if there is none. An
+/// empty separator splits into characters.
+pub fn split(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	reject_flags(&args, "split")?;
+	let mut args = string_args(args, context, "split", "split [separator] string", 1..=2)?;
+	let string = args.pop().unwrap_or_default();
+	let parts: Vec<&str> = match args.pop() {
+		None => string.split_whitespace().collect(),
+		Some(separator) if separator.is_empty() => string.graphemes(true).collect(),
+		Some(separator) => string.split(separator.as_str()).collect(),
+	};
+	Ok(Value::List(
+		parts
+			.into_iter()
+			.map(|part| Value::String(part.to_string()))
+			.collect(),
+	))
+}
+
+/// `join separator list` joins the items of a list, or the lines of a string.
+pub fn join(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	reject_flags(&args, "join")?;
+	if args.len() != 2 {
+		return Err(arity_error("join", "join separator list"));
+	}
+	let separator = value_to_string(evaluate_expression(args.remove(0), context)?, context)?;
+	let items = value_to_list(evaluate_expression(args.remove(0), context)?, context)?;
+	let items = items
+		.into_iter()
+		.map(|item| value_to_string(item, context))
+		.collect::<Result<Vec<String>, ExecutorError>>()?;
+	Ok(Value::String(items.join(&separator)))
+}
+
+/// `lines string` splits a string, such as a command's output, into a list of its lines.
+pub fn lines(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [string] = exactly(args, context, "lines", "lines string")?;
+	Ok(Value::List(
+		string
+			.lines()
+			.map(|line| Value::String(line.to_string()))
+			.collect(),
+	))
+}
+
+/// `words string` splits a string into a list of the words separated by whitespace.
+pub fn words(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [string] = exactly(args, context, "words", "words string")?;
+	Ok(Value::List(
+		string
+			.split_whitespace()
+			.map(|word| Value::String(word.to_string()))
+			.collect(),
+	))
+}
+
+pub fn trim(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [string] = exactly(args, context, "trim", "trim string")?;
+	Ok(Value::String(string.trim().to_string()))
+}
+
+/// `replace from to string` replaces every occurrence of `from`.
+pub fn replace(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [from, to, string] = exactly(args, context, "replace", "replace from to string")?;
+	if from.is_empty() {
+		return Err(string_error("Cannot replace an empty string.", "replace"));
+	}
+	Ok(Value::String(string.replace(&from, &to)))
+}
+
+pub fn upper(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [string] = exactly(args, context, "upper", "upper string")?;
+	Ok(Value::String(string.to_uppercase()))
+}
+
+pub fn lower(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [string] = exactly(args, context, "lower", "lower string")?;
+	Ok(Value::String(string.to_lowercase()))
+}
+
+/// The number of characters in a string, counting what a user sees as one character once, or of
+/// items in a list.
+pub fn len(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 1 {
+		return Err(arity_error("len", "len string"));
+	}
+	let length = match evaluate_expression(args.remove(0), context)? {
+		Value::List(items) => items.len(),
+		value => value_to_string(value, context)?.graphemes(true).count(),
+	};
+	Ok(Value::Int(length as i64))
+}
+
+/// `substr start [length] string` takes the characters from `start`, counting from 0, to the end
+/// or for `length` characters. A negative start counts from the end.
+pub fn substr(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let usage = "substr start [length] string";
+	if !(2..=3).contains(&args.len()) {
+		return Err(arity_error("substr", usage));
+	}
+	let mut args = evaluate_values(args, context)?;
+	let string = value_to_string(args.pop().unwrap_or(Value::Nil), context)?;
+	let graphemes: Vec<&str> = string.graphemes(true).collect();
+	let mut args = args.into_iter();
+	let start = int_arg(args.next(), "substr", context)?;
+	let start = if start < 0 {
+		graphemes
+			.len()
+			.saturating_sub(start.unsigned_abs() as usize)
+	} else {
+		(start as usize).min(graphemes.len())
+	};
+	let end = match args.next() {
+		Some(length) => {
+			let length = int_arg(Some(length), "substr", context)?;
+			if length < 0 {
+				return Err(string_error("Length cannot be negative.", "substr"));
+			}
+			start.saturating_add(length as usize).min(graphemes.len())
+		}
+		None => graphemes.len(),
+	};
+	Ok(Value::String(graphemes[start..end].concat()))
+}
+
+pub fn starts_with(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [prefix, string] = exactly(args, context, "starts-with?", "starts-with? prefix string")?;
+	Ok(Value::Bool(string.starts_with(&prefix)))
+}
+
+pub fn ends_with(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [suffix, string] = exactly(args, context, "ends-with?", "ends-with? suffix string")?;
+	Ok(Value::Bool(string.ends_with(&suffix)))
+}
+
+pub fn contains(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let [part, string] = exactly(args, context, "contains?", "contains? part string")?;
+	Ok(Value::Bool(string.contains(&part)))
+}
+
+/// `pad width [fill] string` pads the end of a string with spaces, or `fill`, until it is `width`
+/// characters long. A negative width pads the start instead, to line up numbers.
+pub fn pad(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let usage = "pad width [fill] string";
+	if !(2..=3).contains(&args.len()) {
+		return Err(arity_error("pad", usage));
+	}
+	let mut args = evaluate_values(args, context)?;
+	let string = value_to_string(args.pop().unwrap_or(Value::Nil), context)?;
+	let mut args = args.into_iter();
+	let width = int_arg(args.next(), "pad", context)?;
+	let fill = match args.next() {
+		Some(fill) => value_to_string(fill, context)?,
+		None => " ".to_string(),
+	};
+	if fill.graphemes(true).count() != 1 {
+		return Err(string_error("The fill must be a single character.", "pad"));
+	}
+	let missing = (width.unsigned_abs() as usize).saturating_sub(string.graphemes(true).count());
+	let padding = fill.repeat(missing);
+	let padded = if width < 0 {
+		padding + &string
+	} else {
+		string + &padding
+	};
+	Ok(Value::String(padded))
+}
+
+fn evaluate_values(
+	args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Vec<Value>, ExecutorError> {
+	args.into_iter()
+		.map(|arg| evaluate_expression(arg, context))
+		.collect()
+}
+
+/// Evaluate the arguments to strings, checking that there are as many as `counts` allows. The
+/// string being worked on comes last in every string builtin, so they can be pipeline stages.
+fn string_args(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	usage: &str,
+	counts: RangeInclusive<usize>,
+) -> Result<Vec<String>, ExecutorError> {
+	if !counts.contains(&args.len()) {
+		return Err(arity_error(builtin, usage));
+	}
+	evaluate_values(args, context)?
+		.into_iter()
+		.map(|value| value_to_string(value, context))
+		.collect()
+}
+
+fn exactly<const N: usize>(
+	args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	usage: &str,
+) -> Result<[String; N], ExecutorError> {
+	let strings = string_args(args, context, builtin, usage, N..=N)?;
+	strings.try_into().map_err(|_| arity_error(builtin, usage))
+}
+
+fn int_arg(value: Option<Value>, builtin: &str, context: &Context) -> Result<i64, ExecutorError> {
+	match value.map(|value| value_to_number(value, builtin, context)) {
+		Some(Ok(Number::Int(int))) => Ok(int),
+		Some(Ok(Number::Float(float))) => Err(string_error(
+			&format!("Expected a whole number but got {float}."),
+			builtin,
+		)),
+		Some(Err(e)) => Err(e),
+		None => {
+			Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+				.binary(builtin.to_string()))
+		}
+	}
+}
+
+fn arity_error(builtin: &str, usage: &str) -> ExecutorError {
+	ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+		.binary(builtin.to_string())
+		.usage(usage)
+}
+
+fn string_error(message: &str, builtin: &str) -> ExecutorError {
+	ExecutorErrorType::BuiltinExecutionError(message.to_string()).binary(builtin.to_string())
+}
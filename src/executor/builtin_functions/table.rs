@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::comparison::Comparable,
+		context::Context,
+		evaluate_expression, evaluate_expression_to_string,
+		function::Function,
+		reject_flags,
+		value::{Record, Value},
+		value_to_data, value_to_list, value_to_string,
+	},
+	parser::Expression,
+};
+
+/// `select column... table` keeps only the given columns of every row, in that order. A row
+/// without one of them gets nil. A single record works as a table of one row.
+pub fn select(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (columns, table) = columns_and_table(args, context, "select", "select column... table")?;
+	map_rows(table, context, "select", |row| {
+		let mut selected = Record::new();
+		for column in &columns {
+			let value = row.get(column).cloned().unwrap_or(Value::Nil);
+			selected.insert(column.clone(), value);
+		}
+		selected
+	})
+}
+
+/// `reject column... table` removes the given columns from every row.
+pub fn reject(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (columns, table) = columns_and_table(args, context, "reject", "reject column... table")?;
+	map_rows(table, context, "reject", |row| {
+		let mut kept = Record::new();
+		for (column, value) in row.iter() {
+			if !columns.contains(column) {
+				kept.insert(column.clone(), value.clone());
+			}
+		}
+		kept
+	})
+}
+
+/// `sort-by [--reverse] key list` sorts by a column, or by what a function returns for each item.
+/// Keys that are numbers, or parse as one, come first in numeric order, then the rest as strings.
+/// Equal keys keep their order.
+pub fn sort_by(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let reverse = args.first() == Some(&Expression::String("--reverse".to_string()));
+	if reverse {
+		args.remove(0);
+	}
+	reject_flags(&args, "sort-by")?;
+	let (key, rows) = key_and_list(args, context, "sort-by", "sort-by [--reverse] key list")?;
+	let mut keyed = rows
+		.into_iter()
+		.map(|row| {
+			let value = key.of(&row, context, "sort-by")?;
+			Ok((Comparable::from_value(value, context)?, row))
+		})
+		.collect::<Result<Vec<(Comparable, Value)>, ExecutorError>>()?;
+	keyed.sort_by(|(a, _), (b, _)| match reverse {
+		true => b.sort_order(a),
+		false => a.sort_order(b),
+	});
+	Ok(Value::List(keyed.into_iter().map(|(_, row)| row).collect()))
+}
+
+/// `group-by key list` returns a record from each key to the list of items with it, in the order
+/// the keys first appear.
+pub fn group_by(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (key, rows) = key_and_list(args, context, "group-by", "group-by key list")?;
+	let mut groups: Vec<(String, Vec<Value>)> = vec![];
+	for row in rows {
+		let value = key.of(&row, context, "group-by")?;
+		let name = value_to_string(value, context)?;
+		match groups.iter_mut().find(|(group, _)| *group == name) {
+			Some((_, items)) => items.push(row),
+			None => groups.push((name, vec![row])),
+		}
+	}
+	let mut record = Record::new();
+	for (name, items) in groups {
+		record.insert(name, Value::List(items));
+	}
+	Ok(Value::Record(record))
+}
+
+/// `uniq list` removes repeated items, keeping the first of each. Strings are only the same if
+/// they are equal, so `007` and `7` are both kept.
+pub fn uniq(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	reject_flags(&args, "uniq")?;
+	if args.len() != 1 {
+		return Err(arity_error("uniq", "uniq list"));
+	}
+	let items = value_to_list(evaluate_expression(args.remove(0), context)?, context)?;
+	let mut seen = HashSet::new();
+	let mut unique = vec![];
+	for item in items {
+		let item = value_to_data(item, context)?;
+		if seen.insert(UniqKey::of(&item)) {
+			unique.push(item);
+		}
+	}
+	Ok(Value::List(unique))
+}
+
+/// `first [n] list` returns the first item, or nil for an empty list, or a list of the first `n`.
+pub fn first(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (count, items) = count_and_list(args, context, "first", "first [n] list")?;
+	Ok(match count {
+		Some(count) => Value::List(items.into_iter().take(count).collect()),
+		None => items.into_iter().next().unwrap_or(Value::Nil),
+	})
+}
+
+/// `last [n] list` returns the last item, or nil for an empty list, or a list of the last `n`.
+pub fn last(args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	let (count, mut items) = count_and_list(args, context, "last", "last [n] list")?;
+	Ok(match count {
+		Some(count) => Value::List(items.split_off(items.len().saturating_sub(count))),
+		None => items.pop().unwrap_or(Value::Nil),
+	})
+}
+
+/// `length list` is the number of items in a list, fields in a record or lines of a string.
+pub fn length(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	reject_flags(&args, "length")?;
+	if args.len() != 1 {
+		return Err(arity_error("length", "length list"));
+	}
+	let length = match evaluate_expression(args.remove(0), context)? {
+		Value::Record(record) => record.iter().count(),
+		value => value_to_list(value, context)?.len(),
+	};
+	Ok(Value::Int(length as i64))
+}
+
+/// What to sort or group by: a column of each row, or the result of calling a function on it.
+enum Key {
+	Column(String),
+	Function(Function),
+}
+
+impl Key {
+	fn of(
+		&self,
+		row: &Value,
+		context: &mut Context,
+		builtin: &str,
+	) -> Result<Value, ExecutorError> {
+		match self {
+			Key::Function(function) => {
+				let value = function.call_with(vec![row.clone()], context)?;
+				value_to_data(value, context)
+			}
+			Key::Column(column) => match row {
+				Value::Record(record) => Ok(record.get(column).cloned().unwrap_or(Value::Nil)),
+				row => Err(table_error(
+					format!("Expected a record with '{column}' but got '{row}'."),
+					builtin,
+				)),
+			},
+		}
+	}
+}
+
+fn key_and_list(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	usage: &str,
+) -> Result<(Key, Vec<Value>), ExecutorError> {
+	if args.len() != 2 {
+		return Err(arity_error(builtin, usage));
+	}
+	let key = match evaluate_expression(args.remove(0), context)? {
+		Value::Lambda(lambda) => Key::Function(lambda),
+		value => Key::Column(value_to_string(value, context)?),
+	};
+	let rows = value_to_list(evaluate_expression(args.remove(0), context)?, context)?;
+	Ok((key, rows))
+}
+
+/// Every argument but the last is a column name, and the last is the table.
+fn columns_and_table(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	usage: &str,
+) -> Result<(Vec<String>, Value), ExecutorError> {
+	let table = match args.pop() {
+		Some(table) if !args.is_empty() => evaluate_expression(table, context)?,
+		_ => return Err(arity_error(builtin, usage)),
+	};
+	let columns = args
+		.into_iter()
+		.map(|arg| evaluate_expression_to_string(arg, context))
+		.collect::<Result<Vec<String>, ExecutorError>>()?;
+	Ok((columns, table))
+}
+
+/// Apply `f` to each row of a table, or to a single record.
+fn map_rows(
+	table: Value,
+	context: &Context,
+	builtin: &str,
+	f: impl Fn(&Record) -> Record,
+) -> Result<Value, ExecutorError> {
+	if let Value::Record(record) = table {
+		return Ok(Value::Record(f(&record)));
+	}
+	let rows = value_to_list(table, context)?
+		.into_iter()
+		.map(|row| match row {
+			Value::Record(record) => Ok(Value::Record(f(&record))),
+			row => Err(table_error(
+				format!("Expected a record but got '{row}'."),
+				builtin,
+			)),
+		})
+		.collect::<Result<Vec<Value>, ExecutorError>>()?;
+	Ok(Value::List(rows))
+}
+
+fn count_and_list(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+	builtin: &str,
+	usage: &str,
+) -> Result<(Option<usize>, Vec<Value>), ExecutorError> {
+	reject_flags(&args, builtin)?;
+	if !(1..=2).contains(&args.len()) {
+		return Err(arity_error(builtin, usage));
+	}
+	let items = value_to_list(evaluate_expression(args.pop().unwrap(), context)?, context)?;
+	let count = match args.pop() {
+		Some(count) => {
+			let count = evaluate_expression_to_string(count, context)?;
+			match count.parse::<usize>() {
+				Ok(count) => Some(count),
+				Err(_) => {
+					return Err(table_error(
+						format!("Expected a count but got '{count}'."),
+						builtin,
+					))
+				}
+			}
+		}
+		None => None,
+	};
+	Ok((count, items))
+}
+
+/// What `uniq` compares: strings exactly, ints and floats by their numeric value, and lists and
+/// records by their items, with fields in any order.
+#[derive(PartialEq, Eq, Hash)]
+enum UniqKey {
+	String(String),
+	Int(i64),
+	/// The bits of a float that isn't a whole number.
+	Float(u64),
+	List(Vec<UniqKey>),
+	Record(Vec<(String, UniqKey)>),
+}
+
+impl UniqKey {
+	fn of(value: &Value) -> UniqKey {
+		match value {
+			Value::Int(int) => UniqKey::Int(*int),
+			Value::Float(float) if float.fract() == 0.0 && float.abs() < i64::MAX as f64 => {
+				UniqKey::Int(*float as i64)
+			}
+			Value::Float(float) => UniqKey::Float(float.to_bits()),
+			Value::List(items) => UniqKey::List(items.iter().map(UniqKey::of).collect()),
+			Value::Record(record) => {
+				let mut fields: Vec<(String, UniqKey)> = record
+					.iter()
+					.map(|(name, value)| (name.clone(), UniqKey::of(value)))
+					.collect();
+				fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+				UniqKey::Record(fields)
+			}
+			value => UniqKey::String(value.to_string()),
+		}
+	}
+}
+
+fn arity_error(builtin: &str, usage: &str) -> ExecutorError {
+	ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+		.binary(builtin.to_string())
+		.usage(usage)
+}
+
+fn table_error(message: String, builtin: &str) -> ExecutorError {
+	ExecutorErrorType::BuiltinExecutionError(message).binary(builtin.to_string())
+}
@@ -0,0 +1,102 @@
+use toml::Table;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context,
+		evaluate_expression, evaluate_expression_to_string,
+		value::{Record, Value},
+		value_to_data,
+	},
+	parser::Expression,
+};
+
+/// `from-toml string` parses a TOML document into a record.
+pub fn from_toml(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 1 {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("from-toml".to_string())
+			.usage("from-toml string"));
+	}
+	let string = evaluate_expression_to_string(args.remove(0), context)?;
+	match string.parse::<Table>() {
+		Ok(table) => Ok(toml_to_value(toml::Value::Table(table))),
+		Err(e) => Err(toml_error(format!("Invalid TOML: {e}"), "from-toml")),
+	}
+}
+
+/// `to-toml record` writes a record as a TOML document.
+pub fn to_toml(mut args: Vec<Expression>, context: &mut Context) -> Result<Value, ExecutorError> {
+	if args.len() != 1 {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("to-toml".to_string())
+			.usage("to-toml record"));
+	}
+	let value = evaluate_expression(args.remove(0), context)?;
+	let table = match value_to_toml(value, context)? {
+		toml::Value::Table(table) => table,
+		value => {
+			return Err(toml_error(
+				format!("A TOML document has to be a record, not '{value}'."),
+				"to-toml",
+			))
+		}
+	};
+	toml::to_string(&table)
+		.map(Value::String)
+		.map_err(|e| toml_error(e.to_string(), "to-toml"))
+}
+
+fn toml_to_value(toml: toml::Value) -> Value {
+	match toml {
+		toml::Value::String(string) => Value::String(string),
+		toml::Value::Integer(int) => Value::Int(int),
+		toml::Value::Float(float) => Value::Float(float),
+		toml::Value::Boolean(bool) => Value::Bool(bool),
+		toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+		toml::Value::Array(items) => Value::List(items.into_iter().map(toml_to_value).collect()),
+		toml::Value::Table(table) => {
+			let mut record = Record::new();
+			for (key, value) in table {
+				record.insert(key, toml_to_value(value));
+			}
+			Value::Record(record)
+		}
+	}
+}
+
+/// Convert a value to TOML, running a command and using its output as a string.
+fn value_to_toml(value: Value, context: &Context) -> Result<toml::Value, ExecutorError> {
+	let toml = match value_to_data(value, context)? {
+		Value::String(string) => toml::Value::String(string),
+		Value::Int(int) => toml::Value::Integer(int),
+		Value::Float(float) => toml::Value::Float(float),
+		Value::Bool(bool) => toml::Value::Boolean(bool),
+		Value::Status(status) => toml::Value::Integer(status.code().into()),
+		Value::List(items) => toml::Value::Array(
+			items
+				.into_iter()
+				.map(|item| value_to_toml(item, context))
+				.collect::<Result<_, _>>()?,
+		),
+		Value::Record(record) => {
+			let mut table = Table::new();
+			for (key, value) in record.iter() {
+				table.insert(key.clone(), value_to_toml(value.clone(), context)?);
+			}
+			toml::Value::Table(table)
+		}
+		Value::Nil => return Err(toml_error("TOML has no nil value.".to_string(), "to-toml")),
+		Value::Lambda(_) | Value::Command(_) => {
+			return Err(toml_error(
+				"Cannot convert a function to TOML.".to_string(),
+				"to-toml",
+			))
+		}
+	};
+	Ok(toml)
+}
+
+fn toml_error(message: String, builtin: &str) -> ExecutorError {
+	ExecutorErrorType::BuiltinExecutionError(message).binary(builtin.to_string())
+}
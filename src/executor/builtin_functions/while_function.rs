@@ -0,0 +1,38 @@
+use std::ops::ControlFlow;
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::{if_function::value_to_bool, loop_function::run_iteration},
+		context::Context,
+		evaluate_expression,
+		status::Status,
+		value::Value,
+	},
+	parser::Expression,
+};
+
+/// `while predicate body...` runs the body for as long as the predicate is true.
+pub fn while_function(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.len() < 2 {
+		return Err(
+			ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction.binary("while".to_string())
+		);
+	}
+	let predicate = args.remove(0);
+	let mut last_status = Status::Exited(0);
+	while value_to_bool(
+		evaluate_expression(predicate.clone(), context)?,
+		"while",
+		context,
+	)? {
+		match run_iteration(&args, None, context)? {
+			ControlFlow::Continue(status) => last_status = status,
+			ControlFlow::Break(status) => return Ok(Value::Status(status)),
+		}
+	}
+	Ok(Value::Status(last_status))
+}
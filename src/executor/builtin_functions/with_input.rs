@@ -0,0 +1,29 @@
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context, evaluate_expression, evaluate_expression_to_string, value::Value,
+	},
+	parser::Expression,
+};
+
+pub fn with_input(
+	mut args: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	if args.len() != 2 {
+		return Err(ExecutorErrorType::IncorrectNumberOfArgsToBuiltinFunction
+			.binary("with-input".to_string()));
+	}
+	let input = evaluate_expression_to_string(args.remove(0), context)?;
+	let mut pipeline = match evaluate_expression(args.remove(0), context)? {
+		Value::Command(pipeline) => pipeline,
+		value => {
+			return Err(ExecutorErrorType::BuiltinExecutionError(format!(
+				"Expected a command but got '{value}'"
+			))
+			.binary("with-input".to_string()))
+		}
+	};
+	pipeline.set_input(input);
+	Ok(Value::Command(pipeline))
+}
@@ -1,15 +1,75 @@
 use std::{collections::HashMap, env, io, path::PathBuf};
 
+use crate::executor::{function::Function, jobs::JobTable, scope::Scope, status::Status};
+
 #[derive(Clone)]
 pub struct Context {
 	pub working_dir: PathBuf,
-	pub vars: HashMap<String, String>,
+	pub vars: Scope,
+	pub last_status: Status,
+	/// The status of every stage of the last pipeline.
+	pub pipe_status: Vec<Status>,
+	pub options: Options,
+	pub jobs: JobTable,
+	/// Functions defined with `defn`, by name.
+	pub functions: HashMap<String, Function>,
 }
 
 impl Context {
 	pub fn new() -> io::Result<Self> {
 		let working_dir = env::current_dir()?;
-		let vars = HashMap::new();
-		Ok(Context { working_dir, vars })
+		let vars = Scope::new();
+		Ok(Context {
+			working_dir,
+			vars,
+			last_status: Status::default(),
+			pipe_status: vec![Status::default()],
+			options: Options::default(),
+			jobs: JobTable::default(),
+			functions: HashMap::new(),
+		})
+	}
+}
+
+/// Settings that change how commands are evaluated, toggled with `set-option`.
+#[derive(Clone)]
+pub struct Options {
+	/// Whether a command substitution that exits with a non-zero status is an error.
+	pub strict_substitution: bool,
+	/// Whether a pipeline fails when any stage fails, rather than only when the last one does.
+	pub pipefail: bool,
+}
+
+impl Options {
+	pub fn set(&mut self, option: ShellOption, value: bool) {
+		match option {
+			ShellOption::StrictSubstitution => self.strict_substitution = value,
+			ShellOption::Pipefail => self.pipefail = value,
+		}
+	}
+}
+
+impl Default for Options {
+	fn default() -> Self {
+		Options {
+			strict_substitution: true,
+			pipefail: false,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellOption {
+	StrictSubstitution,
+	Pipefail,
+}
+
+impl ShellOption {
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"strict-substitution" => Some(ShellOption::StrictSubstitution),
+			"pipefail" => Some(ShellOption::Pipefail),
+			_ => None,
+		}
 	}
 }
@@ -0,0 +1,83 @@
+use std::{fmt::Display, mem};
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		builtin_functions::do_function::evaluate_block, context::Context,
+		evaluate_expression_to_data, scope::Scope, value::Value,
+	},
+	parser::Expression,
+};
+
+/// A function defined with `defn`, or a lambda made with `fn`.
+#[derive(Clone)]
+pub struct Function {
+	pub name: String,
+	pub params: Vec<String>,
+	pub body: Vec<Expression>,
+	/// The variables where the function was made, which the body sees rather than the caller's.
+	pub scope: Scope,
+}
+
+impl Function {
+	/// Call the function with arguments evaluated in the caller's context.
+	pub fn call(
+		&self,
+		args: Vec<Expression>,
+		context: &mut Context,
+	) -> Result<Value, ExecutorError> {
+		let args = args
+			.into_iter()
+			.map(|arg| evaluate_expression_to_data(arg, context))
+			.collect::<Result<Vec<Value>, ExecutorError>>()
+			.map_err(|e| self.error(e))?;
+		self.call_with(args, context)
+	}
+
+	/// Run the body in a new scope with the arguments bound to the parameters. The body is run like
+	/// a `do` block, see `evaluate_block`. A command it returns names the function in errors
+	/// starting it, as it only starts once the caller runs it.
+	pub fn call_with(
+		&self,
+		args: Vec<Value>,
+		context: &mut Context,
+	) -> Result<Value, ExecutorError> {
+		let caller_scope = mem::replace(&mut context.vars, self.scope.clone());
+		context.vars.push();
+		let result = self.run_body(args, context);
+		context.vars = caller_scope;
+		match result.map_err(|e| self.error(e))? {
+			Value::Command(mut pipeline) => {
+				pipeline.returned_from(self.name.clone());
+				Ok(Value::Command(pipeline))
+			}
+			value => Ok(value),
+		}
+	}
+
+	fn run_body(&self, args: Vec<Value>, context: &mut Context) -> Result<Value, ExecutorError> {
+		if args.len() != self.params.len() {
+			let plural = if self.params.len() == 1 { "" } else { "s" };
+			return Err(ExecutorErrorType::BuiltinExecutionError(format!(
+				"Expected {} argument{plural} but got {}.",
+				self.params.len(),
+				args.len()
+			))
+			.to_error());
+		}
+		for (param, value) in self.params.iter().zip(args) {
+			context.vars.insert(param.clone(), value);
+		}
+		evaluate_block(self.body.clone(), context)
+	}
+
+	fn error(&self, error: ExecutorError) -> ExecutorError {
+		ExecutorErrorType::FunctionFailed(Box::new(error)).binary(self.name.clone())
+	}
+}
+
+impl Display for Function {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "fn ({})", self.params.join(" "))
+	}
+}
@@ -0,0 +1,378 @@
+use std::{
+	io,
+	mem::MaybeUninit,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex, MutexGuard, OnceLock,
+	},
+	thread::JoinHandle,
+};
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::status::Status,
+};
+
+/// The shell's own process group and terminal settings, only set when job control is enabled.
+struct ShellTerminal {
+	pgid: libc::pid_t,
+	modes: libc::termios,
+}
+
+/// Ctrl-\ only goes to the foreground job, while the job control signals would stop the shell
+/// itself. Ctrl-C is caught instead, see `catch_interrupts`.
+const SHELL_IGNORED_SIGNALS: [libc::c_int; 4] =
+	[libc::SIGQUIT, libc::SIGTSTP, libc::SIGTTIN, libc::SIGTTOU];
+
+static SHELL_TERMINAL: OnceLock<ShellTerminal> = OnceLock::new();
+
+/// Set by Ctrl-C while the shell itself is in the foreground, such as in a loop of builtins.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Put the shell in its own process group in control of the terminal, ignore the signals that
+/// would otherwise stop it and catch Ctrl-C. Returns whether job control could be enabled.
+pub fn init_job_control() -> bool {
+	unsafe {
+		if libc::isatty(libc::STDIN_FILENO) == 0 {
+			return false;
+		}
+		// Wait until we are in the foreground, as starting job control from the background would
+		// steal the terminal from whoever has it.
+		loop {
+			let pgid = libc::getpgrp();
+			if libc::tcgetpgrp(libc::STDIN_FILENO) == pgid {
+				break;
+			}
+			libc::kill(-pgid, libc::SIGTTIN);
+		}
+		// SIGTTOU has to be ignored before taking the terminal, or the shell would be stopped.
+		for signal in SHELL_IGNORED_SIGNALS {
+			libc::signal(signal, libc::SIG_IGN);
+		}
+		let pgid = libc::getpid();
+		// This fails if the shell is already a session leader, which is fine.
+		libc::setpgid(pgid, pgid);
+		let mut modes = MaybeUninit::<libc::termios>::uninit();
+		if libc::tcsetpgrp(libc::STDIN_FILENO, pgid) != 0
+			|| libc::tcgetattr(libc::STDIN_FILENO, modes.as_mut_ptr()) != 0
+		{
+			// Without job control the shell has to be interruptible like any other program.
+			let _ = reset_child_signals();
+			return false;
+		}
+		let modes = modes.assume_init();
+		catch_interrupts();
+		SHELL_TERMINAL.set(ShellTerminal { pgid, modes }).is_ok()
+	}
+}
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+	INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Let Ctrl-C set a flag rather than kill the shell, for loops and blocks to stop at the next
+/// `check_interrupt`.
+pub fn catch_interrupts() {
+	unsafe {
+		libc::signal(
+			libc::SIGINT,
+			on_interrupt as *const () as libc::sighandler_t,
+		);
+	}
+}
+
+/// Return an `Interrupted` error if Ctrl-C was pressed since the last check.
+pub fn check_interrupt() -> Result<(), ExecutorError> {
+	match INTERRUPTED.swap(false, Ordering::SeqCst) {
+		true => Err(ExecutorErrorType::Interrupted.to_error()),
+		false => Ok(()),
+	}
+}
+
+/// Forget a Ctrl-C that nothing checked for, so it doesn't stop the next line.
+pub fn clear_interrupt() {
+	INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+pub fn job_control_enabled() -> bool {
+	SHELL_TERMINAL.get().is_some()
+}
+
+/// Undo what the shell did to ignore job control signals, run in every child before it execs as
+/// ignored signals stay ignored across `exec`.
+pub fn reset_child_signals() -> io::Result<()> {
+	for signal in std::iter::once(libc::SIGINT).chain(SHELL_IGNORED_SIGNALS) {
+		unsafe {
+			libc::signal(signal, libc::SIG_DFL);
+		}
+	}
+	Ok(())
+}
+
+/// Give the terminal to a process group, if job control is enabled.
+fn give_terminal_to(pgid: libc::pid_t) {
+	if job_control_enabled() {
+		unsafe {
+			libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+		}
+	}
+}
+
+/// Take the terminal back for the shell and restore its settings, which a stopped job may have
+/// left changed.
+fn reclaim_terminal() {
+	if let Some(shell) = SHELL_TERMINAL.get() {
+		unsafe {
+			libc::tcsetpgrp(libc::STDIN_FILENO, shell.pgid);
+			libc::tcsetattr(libc::STDIN_FILENO, libc::TCSADRAIN, &shell.modes);
+		}
+	}
+}
+
+/// A started pipeline, which may be stopped and continued.
+pub struct Job {
+	pub id: usize,
+	pub pgid: libc::pid_t,
+	pub description: String,
+	pub stopped: bool,
+	stages: Vec<JobStage>,
+}
+
+pub enum JobStage {
+	Process(libc::pid_t, Option<Status>),
+	Thread(Option<JoinHandle<Status>>, Option<Status>),
+}
+
+pub enum JobState {
+	Done(Vec<Status>),
+	Stopped(Status),
+}
+
+impl Job {
+	pub fn new(pgid: libc::pid_t, description: String, stages: Vec<JobStage>) -> Self {
+		Job {
+			id: 0,
+			pgid,
+			description,
+			stopped: false,
+			stages,
+		}
+	}
+
+	/// Wait for the job with it in control of the terminal, until it finishes or is stopped.
+	pub fn wait_in_foreground(&mut self) -> JobState {
+		give_terminal_to(self.pgid);
+		let state = self.wait();
+		reclaim_terminal();
+		state
+	}
+
+	/// Continue a stopped job with it in control of the terminal and wait for it.
+	pub fn continue_in_foreground(&mut self) -> JobState {
+		give_terminal_to(self.pgid);
+		self.signal(libc::SIGCONT);
+		self.stopped = false;
+		let state = self.wait();
+		reclaim_terminal();
+		state
+	}
+
+	/// Continue a stopped job without giving it the terminal.
+	pub fn continue_in_background(&mut self) {
+		self.signal(libc::SIGCONT);
+		self.stopped = false;
+	}
+
+	pub fn signal(&self, signal: libc::c_int) -> bool {
+		unsafe { libc::kill(-self.pgid, signal) == 0 }
+	}
+
+	/// Block until every stage has finished or one of the processes is stopped. Threads are only
+	/// joined once every process has exited, as a thread can be blocked on a stopped process, and
+	/// stay in the job to be joined when it is continued.
+	pub fn wait(&mut self) -> JobState {
+		for stage in self.stages.iter_mut() {
+			if let JobStage::Process(pid, status @ None) = stage {
+				match wait_for_pid(*pid, 0) {
+					Some(ChildEvent::Stopped(signal)) => {
+						self.stopped = true;
+						return JobState::Stopped(Status::Stopped(signal));
+					}
+					Some(ChildEvent::Finished(res)) => *status = Some(res),
+					Some(ChildEvent::Continued) | None => *status = Some(Status::Exited(1)),
+				}
+			}
+		}
+		for stage in self.stages.iter_mut() {
+			if let JobStage::Thread(handle, status @ None) = stage {
+				let result = handle.take().map(|handle| handle.join());
+				*status = Some(match result {
+					Some(Ok(res)) => res,
+					_ => Status::Exited(1),
+				});
+			}
+		}
+		JobState::Done(self.statuses())
+	}
+
+	/// Check on the job without blocking, returning whether it has finished.
+	pub fn poll(&mut self) -> bool {
+		let mut done = true;
+		for stage in self.stages.iter_mut() {
+			match stage {
+				JobStage::Process(pid, status @ None) => {
+					let flags = libc::WNOHANG | libc::WCONTINUED;
+					match wait_for_pid(*pid, flags) {
+						Some(ChildEvent::Finished(res)) => *status = Some(res),
+						Some(ChildEvent::Stopped(_)) => {
+							self.stopped = true;
+							done = false;
+						}
+						Some(ChildEvent::Continued) => {
+							self.stopped = false;
+							done = false;
+						}
+						None => done = false,
+					}
+				}
+				JobStage::Thread(handle, status @ None) => match handle.take() {
+					Some(finished) if finished.is_finished() => {
+						*status = Some(finished.join().unwrap_or(Status::Exited(1)))
+					}
+					unfinished => {
+						*handle = unfinished;
+						done = false;
+					}
+				},
+				_ => {}
+			}
+		}
+		done
+	}
+
+	pub fn statuses(&self) -> Vec<Status> {
+		self.stages
+			.iter()
+			.map(|stage| match stage {
+				JobStage::Process(_, status) | JobStage::Thread(_, status) => {
+					status.unwrap_or(Status::Exited(1))
+				}
+			})
+			.collect()
+	}
+}
+
+enum ChildEvent {
+	Finished(Status),
+	Stopped(libc::c_int),
+	Continued,
+}
+
+/// Wait for a child with `waitpid`, also reporting when it is stopped. Returns `None` if there is
+/// nothing to report yet with `WNOHANG`, or the child can't be waited for.
+fn wait_for_pid(pid: libc::pid_t, flags: libc::c_int) -> Option<ChildEvent> {
+	let mut raw_status = 0;
+	loop {
+		let result = unsafe { libc::waitpid(pid, &mut raw_status, flags | libc::WUNTRACED) };
+		if result == -1 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+			continue;
+		}
+		if result <= 0 {
+			return None;
+		}
+		break;
+	}
+	let event = if libc::WIFEXITED(raw_status) {
+		ChildEvent::Finished(Status::Exited(libc::WEXITSTATUS(raw_status)))
+	} else if libc::WIFSIGNALED(raw_status) {
+		ChildEvent::Finished(Status::Signalled(libc::WTERMSIG(raw_status)))
+	} else if libc::WIFSTOPPED(raw_status) {
+		ChildEvent::Stopped(libc::WSTOPSIG(raw_status))
+	} else {
+		ChildEvent::Continued
+	};
+	Some(event)
+}
+
+/// The jobs that have been stopped or put in the background. Clones of a context share the same
+/// table, as there is only one set of processes.
+#[derive(Clone, Default)]
+pub struct JobTable {
+	jobs: Arc<Mutex<Vec<Job>>>,
+}
+
+impl JobTable {
+	fn lock(&self) -> MutexGuard<'_, Vec<Job>> {
+		// A panic while holding the lock can't leave the table half updated.
+		self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Add a job, giving it the lowest free id if it doesn't have one yet.
+	pub fn add(&self, mut job: Job) -> usize {
+		let mut jobs = self.lock();
+		if job.id == 0 {
+			job.id = (1..)
+				.find(|id| jobs.iter().all(|j| j.id != *id))
+				.unwrap_or(1);
+		}
+		let id = job.id;
+		jobs.push(job);
+		jobs.sort_by_key(|job| job.id);
+		id
+	}
+
+	/// Remove a job from the table, or the most recent one if `id` is `None`.
+	pub fn take(&self, id: Option<usize>) -> Option<Job> {
+		let mut jobs = self.lock();
+		let index = match id {
+			Some(id) => jobs.iter().position(|job| job.id == id)?,
+			None => jobs.len().checked_sub(1)?,
+		};
+		Some(jobs.remove(index))
+	}
+
+	/// Run something with a job without removing it from the table.
+	pub fn with_job<T>(&self, id: Option<usize>, f: impl FnOnce(&mut Job) -> T) -> Option<T> {
+		let mut jobs = self.lock();
+		let job = match id {
+			Some(id) => jobs.iter_mut().find(|job| job.id == id)?,
+			None => jobs.last_mut()?,
+		};
+		Some(f(job))
+	}
+
+	/// Update every job and describe them, removing the ones that have finished.
+	pub fn report(&self) -> Vec<String> {
+		let mut jobs = self.lock();
+		let mut lines = vec![];
+		jobs.retain_mut(|job| {
+			let done = job.poll();
+			let state = match (done, job.stopped) {
+				(true, _) => "Done",
+				(false, true) => "Stopped",
+				(false, false) => "Running",
+			};
+			lines.push(format!("[{}] {state}\t{}", job.id, job.description));
+			!done
+		});
+		lines
+	}
+
+	/// Remove the jobs that have finished and describe them, so the user can be told.
+	pub fn reap(&self) -> Vec<String> {
+		let mut jobs = self.lock();
+		let mut lines = vec![];
+		jobs.retain_mut(|job| {
+			if job.poll() {
+				lines.push(format!("[{}] Done\t{}", job.id, job.description));
+				return false;
+			}
+			true
+		});
+		lines
+	}
+
+	pub fn ids(&self) -> Vec<usize> {
+		self.lock().iter().map(|job| job.id).collect()
+	}
+}
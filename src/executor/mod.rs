@@ -1,124 +1,397 @@
-use std::{
-	path::PathBuf,
-	process::{self, Command},
-};
+use std::process::Command;
 
 use crate::{
 	errors::{ExecutorError, ExecutorErrorType},
 	executor::{
 		builtin_functions::{
-			cd::evaluate_cd, get_env::get_env, if_function::evaluate_if,
-			let_function::let_function, pipe::evaluate_pipe, set_env::set_env,
+			arithmetic::{abs, add, divide, max, min, modulo, multiply, pow, subtract},
+			capture::{capture, raw},
+			cd::evaluate_cd,
+			comparison::{equal, greater, greater_or_equal, less, less_or_equal, not_equal},
+			cond::cond,
+			csv_functions::{from_csv, to_csv},
+			defn::defn,
+			do_function::do_function,
+			fn_function::fn_function,
+			for_function::for_function,
+			format::format,
+			get::get,
+			get_env::get_env,
+			higher_order::{all, any, each, filter, map, reduce},
+			if_function::evaluate_if,
+			job_control::{bg, fg, jobs, kill, wait},
+			json::{from_json, to_json},
+			let_function::let_function,
+			list::list,
+			logic::{and, not, or},
+			loop_function::{break_function, continue_function, loop_function},
+			match_function::match_function,
+			pipe::evaluate_pipe,
+			regex_functions::{re_find, re_find_all, re_match, re_replace, re_split},
+			set_env::set_env,
+			set_function::set_function,
+			set_option::set_option,
+			status::{pipe_status, status},
+			strings::{
+				contains, ends_with, join, len, lines, lower, pad, replace, split, starts_with,
+				substr, trim, upper, words,
+			},
+			table::{first, group_by, last, length, reject, select, sort_by, uniq},
+			toml_functions::{from_toml, to_toml},
+			while_function::while_function,
+			with_input::with_input,
 		},
 		context::Context,
+		jobs::{clear_interrupt, Job, JobState},
+		pipeline::Pipeline,
+		status::Status,
+		value::Value,
 	},
 	parser::{Expression, Func},
 };
 
 mod builtin_functions;
 pub mod context;
-
-enum Value {
-	Command(process::Command),
-	String(String),
-	Cd(PathBuf),
-	Let(String, String),
-}
+pub mod function;
+pub mod jobs;
+pub mod pipeline;
+pub mod scope;
+pub mod status;
+pub mod value;
 
 pub fn execute(func: Func, context: &mut Context) {
-	match execute_with_result(func, context) {
-		Ok(()) => {}
-		Err(e) => eprintln!("{e}"),
-	}
+	clear_interrupt();
+	let statuses = match execute_with_result(func, context) {
+		Ok(statuses) => statuses,
+		Err(e) => {
+			eprintln!("{e}");
+			vec![e.status()]
+		}
+	};
+	context.last_status = Status::of_pipeline(&statuses, context.options.pipefail);
+	context.pipe_status = statuses;
 }
 
-fn execute_with_result(func: Func, context: &mut Context) -> Result<(), ExecutorError> {
-	let command_or_string = evaluate_func(Box::new(func), context)?;
+/// Returns the status of every stage if a pipeline was run, otherwise just the one status.
+fn execute_with_result(func: Func, context: &mut Context) -> Result<Vec<Status>, ExecutorError> {
+	let value = evaluate_func(func, context)?;
+	run_value(value, context)
+}
 
-	let mut command = match command_or_string {
-		Value::Command(command) => command,
-		Value::String(string) => {
-			println!("{string}");
-			return Ok(());
-		}
-		Value::Cd(path) => {
-			context.working_dir = path;
-			return Ok(());
-		}
-		Value::Let(key, value) => {
-			context.vars.insert(key, value);
-			return Ok(());
+/// Print data or run a command, the way a line typed at the prompt is run.
+fn run_value(value: Value, context: &Context) -> Result<Vec<Status>, ExecutorError> {
+	let pipeline = match value {
+		Value::Command(pipeline) => pipeline,
+		Value::Status(status) => return Ok(vec![status]),
+		Value::Nil => return Ok(vec![Status::Exited(0)]),
+		value => {
+			println!("{}", value_to_string(value, context)?);
+			return Ok(vec![Status::Exited(0)]);
 		}
 	};
 
-	let mut child = match command.spawn() {
-		Ok(child) => child,
-		Err(e) => {
-			let binary_name = command.get_program().to_string_lossy().to_string();
-			return Err(ExecutorError::from(e).with(binary_name));
+	let mut job = pipeline.start()?;
+	let state = job.wait_in_foreground();
+	Ok(finish_foreground_job(job, state, context))
+}
+
+/// Get the statuses of a job that was running in the foreground, or move it to the job table if
+/// it was stopped.
+fn finish_foreground_job(job: Job, state: JobState, context: &Context) -> Vec<Status> {
+	match state {
+		JobState::Done(statuses) => {
+			if statuses.contains(&Status::Signalled(libc::SIGINT)) {
+				eprintln!("\ninterrupted");
+			}
+			statuses
 		}
-	};
-	let exit_code = child.wait();
-	exit_code.expect("Error, command was not running");
-	Ok(())
+		JobState::Stopped(status) => {
+			let description = job.description.clone();
+			let id = context.jobs.add(job);
+			eprintln!("\n[{id}] Stopped\t{description}");
+			vec![status]
+		}
+	}
 }
 
 fn evaluate_expression_to_string(
 	expr: Expression,
-	context: &Context,
+	context: &mut Context,
 ) -> Result<String, ExecutorError> {
-	match evaluate_expression(expr, context)? {
-		Value::Command(mut command) => {
-			let child = match command.output() {
-				Ok(child) => child,
-				Err(e) => {
-					let binary_name = command.get_program().to_string_lossy().to_string();
-					return Err(ExecutorError::from(e).with(binary_name));
-				}
-			};
-			Ok(String::from_utf8_lossy(&child.stdout).trim().into())
-		}
+	value_to_string(evaluate_expression(expr, context)?, context)
+}
+
+fn value_to_string(value: Value, context: &Context) -> Result<String, ExecutorError> {
+	match value {
+		Value::Command(pipeline) => substitute_command(pipeline, context),
+		Value::Status(status) => substitute_status(status, context),
 		Value::String(string) => Ok(string),
-		Value::Cd(_) => Err(only_outermost_error("cd")),
-		Value::Let(_, _) => Err(only_outermost_error("let")),
+		Value::Int(_)
+		| Value::Float(_)
+		| Value::Bool(_)
+		| Value::Nil
+		| Value::Record(_)
+		| Value::List(_)
+		| Value::Lambda(_) => Ok(value.to_string()),
+	}
+}
+
+/// Evaluate an expression into a value that can be stored, running any command and using its
+/// output.
+fn evaluate_expression_to_data(
+	expr: Expression,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	value_to_data(evaluate_expression(expr, context)?, context)
+}
+
+fn value_to_data(value: Value, context: &Context) -> Result<Value, ExecutorError> {
+	match value {
+		Value::Command(pipeline) => Ok(Value::String(substitute_command(pipeline, context)?)),
+		Value::Status(status) => Ok(Value::String(substitute_status(status, context)?)),
+		value => Ok(value),
+	}
+}
+
+/// The items of a list, or the lines of any other value, such as a command's output.
+fn value_to_list(value: Value, context: &Context) -> Result<Vec<Value>, ExecutorError> {
+	match value {
+		Value::List(items) => Ok(items),
+		value => Ok(value_to_string(value, context)?
+			.lines()
+			.map(|line| Value::String(line.to_string()))
+			.collect()),
+	}
+}
+
+/// Return an error for a literal argument like `-c`, in builtins named like a common command, so
+/// a flag meant for the command isn't taken as data. Numbers like `-1` and a lone `-` are fine.
+fn reject_flags(args: &[Expression], builtin: &str) -> Result<(), ExecutorError> {
+	for arg in args {
+		if let Expression::String(arg) = arg {
+			let name = arg.trim_start_matches('-');
+			if name.len() < arg.len() && name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+				return Err(ExecutorErrorType::UnknownFlag(arg.clone()).binary(builtin.to_string()));
+			}
+		}
 	}
+	Ok(())
 }
 
-fn only_outermost_error(function: &str) -> ExecutorError {
-	ExecutorError::from_type(ExecutorErrorType::BuiltinExecutionError(format!(
-		"Cannot use {function} as a value. {function} can only be used as the outermost function."
-	)))
-	.with(function.to_string())
+/// Run a command and return what it printed to stdout, without surrounding whitespace.
+fn substitute_command(pipeline: Pipeline, context: &Context) -> Result<String, ExecutorError> {
+	Ok(command_stdout(pipeline, context)?.trim().into())
 }
 
-fn evaluate_expression(expr: Expression, context: &Context) -> Result<Value, ExecutorError> {
+/// The status of a builtin run for its effects, like `let` or a loop, stands for commands that
+/// already ran and printed nothing. Like a failed command, a failed status is an error with
+/// strict substitution.
+fn substitute_status(status: Status, context: &Context) -> Result<String, ExecutorError> {
+	if context.options.strict_substitution && !status.success() {
+		return Err(ExecutorErrorType::SubstitutionFailed(status, String::new()).to_error());
+	}
+	Ok(String::new())
+}
+
+/// Run a command and return exactly what it printed to stdout.
+fn command_stdout(pipeline: Pipeline, context: &Context) -> Result<String, ExecutorError> {
+	let name = pipeline.name();
+	let output = pipeline.output()?;
+	let status = Status::of_pipeline(&output.statuses, context.options.pipefail);
+	if context.options.strict_substitution && !status.success() {
+		let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+		return Err(ExecutorErrorType::SubstitutionFailed(status, stderr).binary(name));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).into())
+}
+
+fn evaluate_expression(expr: Expression, context: &mut Context) -> Result<Value, ExecutorError> {
 	let string = match expr {
 		Expression::String(str) => Value::String(str),
-		Expression::Function(func) => evaluate_func(func, context)?,
+		Expression::Function(func) => evaluate_func(*func, context)?,
 		Expression::Variable(var) => get_var(var, context)?,
+		Expression::Spread(expr) => {
+			let value = evaluate_expression(*expr, context)?;
+			Value::List(value_to_list(value, context)?)
+		}
 	};
 	Ok(string)
 }
 
-fn evaluate_func(func: Box<Func>, context: &Context) -> Result<Value, ExecutorError> {
-	let name = evaluate_expression_to_string(func.name, context)?;
+/// The signature shared by every builtin, which gets its arguments unevaluated.
+pub type Builtin = fn(Vec<Expression>, &mut Context) -> Result<Value, ExecutorError>;
+
+/// Every builtin by name, which is what calls, pipelines, `defn` and completion look names up in.
+/// An alias is another entry with the same function.
+pub const BUILTINS: &[(&str, Builtin)] = &[
+	("if", evaluate_if),
+	("cond", cond),
+	("match", match_function),
+	("split", split),
+	("join", join),
+	("trim", trim),
+	("replace", replace),
+	("upper", upper),
+	("lower", lower),
+	("len", len),
+	("substr", substr),
+	("starts-with?", starts_with),
+	("ends-with?", ends_with),
+	("contains?", contains),
+	("pad", pad),
+	("lines", lines),
+	("words", words),
+	("re-match?", re_match),
+	("re-find", re_find),
+	("re-find-all", re_find_all),
+	("re-replace", re_replace),
+	("re-split", re_split),
+	("format", format),
+	("from-json", from_json),
+	("to-json", to_json),
+	("from-csv", from_csv),
+	("to-csv", to_csv),
+	("from-toml", from_toml),
+	("to-toml", to_toml),
+	("select", select),
+	("reject", reject),
+	("sort-by", sort_by),
+	("group-by", group_by),
+	("uniq", uniq),
+	("first", first),
+	("last", last),
+	("length", length),
+	("pipe", evaluate_pipe),
+	("|", evaluate_pipe),
+	("cd", evaluate_cd),
+	("set-env", set_env),
+	("get-env", get_env),
+	("let", let_function),
+	("set!", set_function),
+	("defn", defn),
+	("fn", fn_function),
+	("do", do_function),
+	("begin", do_function),
+	("for", for_function),
+	("while", while_function),
+	("loop", loop_function),
+	("break", break_function),
+	("continue", continue_function),
+	("+", add),
+	("-", subtract),
+	("*", multiply),
+	("/", divide),
+	("mod", modulo),
+	("pow", pow),
+	("min", min),
+	("max", max),
+	("abs", abs),
+	("=", equal),
+	("!=", not_equal),
+	("<", less),
+	("<=", less_or_equal),
+	(">", greater),
+	(">=", greater_or_equal),
+	("and", and),
+	("or", or),
+	("not", not),
+	("list", list),
+	("map", map),
+	("filter", filter),
+	("where", filter),
+	("reduce", reduce),
+	("each", each),
+	("any", any),
+	("all", all),
+	("status", status),
+	("pipe-status", pipe_status),
+	("set-option", set_option),
+	("complete", capture),
+	("capture", capture),
+	("raw", raw),
+	("get", get),
+	("with-input", with_input),
+	("jobs", jobs),
+	("fg", fg),
+	("bg", bg),
+	("wait", wait),
+	("kill", kill),
+];
+
+pub fn builtin(name: &str) -> Option<Builtin> {
+	BUILTINS
+		.iter()
+		.find(|(builtin, _)| *builtin == name)
+		.map(|(_, function)| *function)
+}
+
+fn evaluate_func(func: Func, context: &mut Context) -> Result<Value, ExecutorError> {
+	let func = expand_spreads(func, context)?;
+	let name = match evaluate_expression(func.name, context)? {
+		Value::Lambda(lambda) => return lambda.call(func.arguments, context),
+		value => value_to_string(value, context)?,
+	};
 	let result_string = match name.as_str() {
 		"" => Value::String("".to_string()),
-		"if" => evaluate_if(func.arguments, context)?,
-		"pipe" | "|" => evaluate_pipe(func.arguments, context)?,
-		"cd" => evaluate_cd(func.arguments, context)?,
-		"set-env" => set_env(func.arguments, context)?,
-		"get-env" => get_env(func.arguments, context)?,
-		"let" => let_function(func.arguments, context)?,
-		command => Value::Command(evalute_command(command, func.arguments, context)?),
+		// `^name` skips builtins and functions, for commands that share a name with one.
+		name if name.len() > 1 && name.starts_with('^') => {
+			Value::Command(evalute_command(&name[1..], func.arguments, context)?.into())
+		}
+		name => match builtin(name) {
+			Some(builtin) => builtin(func.arguments, context)?,
+			None => match context.functions.get(name).cloned() {
+				Some(function) => function.call(func.arguments, context)?,
+				None => Value::Command(evalute_command(name, func.arguments, context)?.into()),
+			},
+		},
 	};
 	Ok(result_string)
 }
 
+/// Replace each `...` argument with the items of its list, or the lines of a command's output, as
+/// separate arguments. A spread name gives the name and the first arguments.
+fn expand_spreads(func: Func, context: &mut Context) -> Result<Func, ExecutorError> {
+	let mut expressions = vec![];
+	for expr in std::iter::once(func.name).chain(func.arguments) {
+		match expr {
+			Expression::Spread(_) => {
+				for item in value_to_list(evaluate_expression(expr, context)?, context)? {
+					expressions.push(Expression::String(value_to_string(item, context)?));
+				}
+			}
+			expr => expressions.push(expr),
+		}
+	}
+	let mut expressions = expressions.into_iter();
+	Ok(Func {
+		name: expressions
+			.next()
+			.unwrap_or(Expression::String(String::new())),
+		arguments: expressions.collect(),
+	})
+}
+
+/// Call a builtin with the output of the previous pipeline stage as `$in`, which is added as the
+/// last argument unless the call already uses it somewhere, even inside a nested call.
+fn evaluate_func_with_input(
+	mut func: Func,
+	input: Value,
+	context: &mut Context,
+) -> Result<Value, ExecutorError> {
+	context.vars.push();
+	context.vars.insert("in".to_string(), input);
+	if !func.arguments.iter().any(|arg| arg.uses_variable("in")) {
+		func.arguments.push(Expression::Variable("in".to_string()));
+	}
+	let result = evaluate_func(func, context);
+	context.vars.pop();
+	result
+}
+
 fn evalute_command(
 	name: &str,
 	args: Vec<Expression>,
-	context: &Context,
+	context: &mut Context,
 ) -> Result<Command, ExecutorError> {
 	let args = evaluate_args(args, context)?;
 	let mut command = Command::new(name);
@@ -127,16 +400,21 @@ fn evalute_command(
 	Ok(command)
 }
 
-fn evaluate_args(func: Vec<Expression>, context: &Context) -> Result<Vec<String>, ExecutorError> {
+fn evaluate_args(
+	func: Vec<Expression>,
+	context: &mut Context,
+) -> Result<Vec<String>, ExecutorError> {
 	func.into_iter()
 		.map(|e| evaluate_expression_to_string(e, context))
 		.collect()
 }
 
 fn get_var(var: String, context: &Context) -> Result<Value, ExecutorError> {
+	if var == "?" {
+		return Ok(Value::Int(context.last_status.code().into()));
+	}
 	match context.vars.get(&var) {
-		//TODO Maybe don't copy here?
-		Some(value) => Ok(Value::String(value.to_string())),
+		Some(value) => Ok(value),
 		None => Err(ExecutorError::from_type(
 			ExecutorErrorType::VariableNotFound(var),
 		)),
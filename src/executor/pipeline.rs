@@ -0,0 +1,487 @@
+use std::{
+	fmt::Display,
+	io::{self, PipeReader, PipeWriter, Read, Write},
+	iter, mem,
+	os::unix::process::CommandExt,
+	process::{Child, ChildStdout, Command, ExitStatus, Stdio},
+	sync::mpsc::{self, Receiver, SyncSender},
+	thread::{self, JoinHandle},
+};
+
+use crate::{
+	errors::{ExecutorError, ExecutorErrorType},
+	executor::{
+		context::Context,
+		evaluate_func_with_input,
+		jobs::{job_control_enabled, reset_child_signals, Job, JobStage},
+		status::Status,
+		value::Value,
+		value_to_string,
+	},
+	parser::{Expression, Func},
+};
+
+/// One or more stages, each with its output connected to the input of the next. Nothing is
+/// started until the pipeline is run, and running it waits for every stage.
+///
+/// The first stage is always a command, the rest can also be builtins which run on their own
+/// thread.
+pub struct Pipeline {
+	first: Command,
+	rest: Vec<Stage>,
+	/// Text written to the first command's stdin, kept here rather than on the command so a
+	/// clone of the pipeline is given the same input.
+	input: Option<String>,
+	/// The functions whose body returned the pipeline, innermost first, which name errors
+	/// starting it like any other error in their body.
+	functions: Vec<String>,
+}
+
+enum Stage {
+	Command(Command),
+	/// A builtin called with the output of the previous stage as `$in`.
+	Builtin(Func, Context),
+}
+
+pub struct PipelineOutput {
+	pub stdout: Vec<u8>,
+	/// The stderr of every command, interleaved in the order it was written.
+	pub stderr: Vec<u8>,
+	pub statuses: Vec<Status>,
+}
+
+impl Pipeline {
+	/// Add the stages of another pipeline. Errors are no longer only the functions' that returned
+	/// the first one, so they aren't named in them anymore.
+	pub fn extend(&mut self, pipeline: Pipeline) {
+		self.rest.push(Stage::Command(pipeline.first));
+		self.rest.extend(pipeline.rest);
+		self.functions.clear();
+	}
+
+	/// Name the function in errors starting the pipeline, as it was returned from its body.
+	pub fn returned_from(&mut self, function: String) {
+		self.functions.push(function);
+	}
+
+	pub fn push_builtin(&mut self, func: Func, context: Context) {
+		self.rest.push(Stage::Builtin(func, context));
+	}
+
+	/// Give the first command `input` on its stdin. Like a here-string, a trailing newline is
+	/// added if there isn't one.
+	pub fn set_input(&mut self, mut input: String) {
+		if !input.ends_with('\n') {
+			input.push('\n');
+		}
+		self.input = Some(input);
+	}
+
+	/// The programs in the pipeline, used to label errors.
+	pub fn name(&self) -> String {
+		let mut names = vec![program_name(&self.first)];
+		names.extend(self.rest.iter().map(Stage::name));
+		names.join(" | ")
+	}
+
+	fn into_stages(self) -> (Vec<Stage>, Option<String>) {
+		let stages = iter::once(Stage::Command(self.first))
+			.chain(self.rest)
+			.collect();
+		(stages, self.input)
+	}
+
+	/// Start the pipeline as a job, with the last stage writing straight to the terminal. If job
+	/// control is enabled the commands are put in their own process group.
+	pub fn start(self) -> Result<Job, ExecutorError> {
+		let description = self.to_string();
+		let functions = self.functions.clone();
+		let (stages, input) = self.into_stages();
+		let (running, _) =
+			start_stages(stages, input, false).map_err(|e| in_functions(e, functions))?;
+		let mut pgid = 0;
+		let stages = running
+			.into_iter()
+			.map(|stage| match stage {
+				RunningStage::Child(child) => {
+					let pid = child.id() as libc::pid_t;
+					if pgid == 0 {
+						pgid = pid;
+					}
+					JobStage::Process(pid, None)
+				}
+				RunningStage::Thread(handle) => JobStage::Thread(Some(handle), None),
+			})
+			.collect();
+		Ok(Job::new(pgid, description, stages))
+	}
+
+	/// Run the pipeline, capturing the stdout of the last stage and the stderr of every command.
+	pub fn output(self) -> Result<PipelineOutput, ExecutorError> {
+		let (mut stderr_reader, stderr_writer) = io::pipe()?;
+		let functions = self.functions.clone();
+		let (mut stages, input) = self.into_stages();
+		for stage in stages.iter_mut() {
+			if let Stage::Command(command) = stage {
+				command.stderr(stderr_writer.try_clone()?);
+			}
+		}
+		// Only the children may hold the write end, otherwise reading would never finish.
+		drop(stderr_writer);
+
+		let (running, captured) =
+			start_stages(stages, input, true).map_err(|e| in_functions(e, functions))?;
+		let stderr_thread = thread::spawn(move || {
+			let mut stderr = vec![];
+			stderr_reader.read_to_end(&mut stderr).map(|_| stderr)
+		});
+		let mut stdout = vec![];
+		let stdout_result = match captured {
+			Some(mut pipe) => pipe.read_to_end(&mut stdout),
+			None => Ok(0),
+		};
+		let statuses = wait_all(running)?;
+		stdout_result?;
+		let stderr = match stderr_thread.join() {
+			Ok(res) => res?,
+			Err(_) => vec![],
+		};
+		Ok(PipelineOutput {
+			stdout,
+			stderr,
+			statuses,
+		})
+	}
+}
+
+impl Stage {
+	fn name(&self) -> String {
+		match self {
+			Stage::Command(command) => program_name(command),
+			Stage::Builtin(func, _) => match &func.name {
+				Expression::String(name) => name.to_string(),
+				_ => "builtin".to_string(),
+			},
+		}
+	}
+}
+
+/// Wrap an error like `Function::error` does for each function, the innermost first.
+fn in_functions(error: ExecutorError, functions: Vec<String>) -> ExecutorError {
+	functions.into_iter().fold(error, |error, function| {
+		ExecutorErrorType::FunctionFailed(Box::new(error)).binary(function)
+	})
+}
+
+fn program_name(command: &Command) -> String {
+	command.get_program().to_string_lossy().to_string()
+}
+
+impl Clone for Stage {
+	fn clone(&self) -> Self {
+		match self {
+			Stage::Command(command) => Stage::Command(clone_command(command)),
+			Stage::Builtin(func, context) => Stage::Builtin(func.clone(), context.clone()),
+		}
+	}
+}
+
+impl Clone for Pipeline {
+	fn clone(&self) -> Self {
+		Pipeline {
+			first: clone_command(&self.first),
+			rest: self.rest.clone(),
+			input: self.input.clone(),
+			functions: self.functions.clone(),
+		}
+	}
+}
+
+/// `Command` doesn't implement `Clone`, so build a new one with the same program, arguments,
+/// working directory and environment. Those are all that is set on a command before it starts,
+/// its input and output and process group are set up by `start_stages`.
+fn clone_command(command: &Command) -> Command {
+	let mut new_command = Command::new(command.get_program());
+	new_command.args(command.get_args());
+	if let Some(dir) = command.get_current_dir() {
+		new_command.current_dir(dir);
+	}
+	for (key, value) in command.get_envs() {
+		match value {
+			Some(value) => new_command.env(key, value),
+			None => new_command.env_remove(key),
+		};
+	}
+	new_command
+}
+
+impl From<Command> for Pipeline {
+	fn from(value: Command) -> Self {
+		Pipeline {
+			first: value,
+			rest: vec![],
+			input: None,
+			functions: vec![],
+		}
+	}
+}
+
+impl Display for Pipeline {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write_command(f, &self.first)?;
+		for stage in &self.rest {
+			write!(f, " | ")?;
+			match stage {
+				Stage::Command(command) => write_command(f, command)?,
+				builtin => write!(f, "{}", builtin.name())?,
+			}
+		}
+		Ok(())
+	}
+}
+
+fn write_command(f: &mut std::fmt::Formatter<'_>, command: &Command) -> std::fmt::Result {
+	write!(f, "{}", program_name(command))?;
+	for arg in command.get_args() {
+		write!(f, " {}", arg.to_string_lossy())?;
+	}
+	Ok(())
+}
+
+enum RunningStage {
+	Child(Child),
+	Thread(JoinHandle<Status>),
+}
+
+/// Where a stage reads its input from.
+enum Upstream {
+	Terminal,
+	Child(ChildStdout),
+	Pipe(PipeReader),
+	Value(Receiver<Value>),
+}
+
+/// The output of the last stage when it is being captured.
+type Captured = Box<dyn Read>;
+
+/// Where a builtin stage sends its result.
+enum Downstream {
+	Terminal,
+	Pipe(PipeWriter),
+	Value(SyncSender<Value>),
+}
+
+/// Start every stage, connecting each one's output to the next one's input, and the first one's
+/// input to `input` if there is any. If `capture` is set the last stage's output is returned to
+/// be read instead of going to the terminal.
+fn start_stages(
+	stages: Vec<Stage>,
+	input: Option<String>,
+	capture: bool,
+) -> Result<(Vec<RunningStage>, Option<Captured>), ExecutorError> {
+	let stage_count = stages.len();
+	let next_is_builtin: Vec<bool> = stages
+		.iter()
+		.skip(1)
+		.map(|stage| matches!(stage, Stage::Builtin(_, _)))
+		.chain(iter::once(false))
+		.collect();
+	let mut running = vec![];
+	let mut upstream = match input {
+		Some(input) => Upstream::Pipe(feed_input(input)?),
+		None => Upstream::Terminal,
+	};
+	// Captured pipelines aren't jobs, so they stay in the shell's process group.
+	let mut pgid = match !capture && job_control_enabled() {
+		true => Some(0),
+		false => None,
+	};
+
+	for (i, stage) in stages.into_iter().enumerate() {
+		let to_terminal = i + 1 == stage_count && !capture;
+		let input = mem::replace(&mut upstream, Upstream::Terminal);
+		let started = match stage {
+			Stage::Command(command) => {
+				start_command(command, input, to_terminal, pgid).map(|mut child| {
+					if let Some(stdout) = child.stdout.take() {
+						upstream = Upstream::Child(stdout);
+					}
+					if let Some(group) = pgid {
+						let group = if group == 0 { child.id() } else { group };
+						// Also set the group from the parent, so it exists before the terminal is
+						// given to it no matter which process runs first.
+						unsafe {
+							libc::setpgid(child.id() as libc::pid_t, group as libc::pid_t);
+						}
+						pgid = Some(group);
+					}
+					RunningStage::Child(child)
+				})
+			}
+			Stage::Builtin(func, context) => builtin_downstream(to_terminal, next_is_builtin[i])
+				.map(|(downstream, output)| {
+					upstream = output;
+					start_builtin(func, context, input, downstream)
+				}),
+		};
+		match started {
+			Ok(stage) => running.push(stage),
+			Err(e) => {
+				// Waiting stops the stages that did start being left as zombies.
+				let _ = wait_all(running);
+				return Err(e);
+			}
+		}
+	}
+
+	// Whatever the last stage was writing to is what gets captured.
+	let captured: Option<Captured> = match upstream {
+		Upstream::Child(stdout) => Some(Box::new(stdout)),
+		Upstream::Pipe(reader) => Some(Box::new(reader)),
+		Upstream::Terminal | Upstream::Value(_) => None,
+	};
+	Ok((running, captured))
+}
+
+/// Where a builtin stage should send its result, and where the next stage should read it from.
+fn builtin_downstream(
+	to_terminal: bool,
+	next_is_builtin: bool,
+) -> Result<(Downstream, Upstream), ExecutorError> {
+	if to_terminal {
+		return Ok((Downstream::Terminal, Upstream::Terminal));
+	}
+	if next_is_builtin {
+		let (sender, receiver) = mpsc::sync_channel(1);
+		return Ok((Downstream::Value(sender), Upstream::Value(receiver)));
+	}
+	let (reader, writer) = io::pipe()?;
+	Ok((Downstream::Pipe(writer), Upstream::Pipe(reader)))
+}
+
+/// Spawn a command, putting it in the process group `pgid` if given, or a new group if that is 0.
+fn start_command(
+	mut command: Command,
+	upstream: Upstream,
+	to_terminal: bool,
+	pgid: Option<u32>,
+) -> Result<Child, ExecutorError> {
+	match upstream {
+		Upstream::Terminal => {}
+		Upstream::Child(stdout) => {
+			command.stdin(Stdio::from(stdout));
+		}
+		Upstream::Pipe(reader) => {
+			command.stdin(reader);
+		}
+		// A builtin only sends values when the next stage is also a builtin.
+		Upstream::Value(_) => {
+			command.stdin(Stdio::null());
+		}
+	}
+	if !to_terminal {
+		command.stdout(Stdio::piped());
+	}
+	if let Some(pgid) = pgid {
+		command.process_group(pgid as i32);
+	}
+	unsafe {
+		command.pre_exec(reset_child_signals);
+	}
+	match command.spawn() {
+		Ok(child) => Ok(child),
+		Err(e) => {
+			let binary_name = command.get_program().to_string_lossy().to_string();
+			Err(ExecutorError::from(e).with(binary_name))
+		}
+	}
+}
+
+/// Write `input` into a pipe from another thread, so input larger than the pipe buffer doesn't
+/// block starting the pipeline.
+fn feed_input(input: String) -> Result<PipeReader, ExecutorError> {
+	let (reader, mut writer) = io::pipe()?;
+	thread::spawn(move || {
+		// The command is free to exit without reading all of its input.
+		let _ = writer.write_all(input.as_bytes());
+	});
+	Ok(reader)
+}
+
+fn start_builtin(
+	func: Func,
+	mut context: Context,
+	upstream: Upstream,
+	downstream: Downstream,
+) -> RunningStage {
+	RunningStage::Thread(thread::spawn(move || {
+		let result = read_upstream(upstream)
+			.and_then(|input| evaluate_func_with_input(func, input, &mut context))
+			.and_then(|value| send_downstream(value, downstream, &context));
+		match result {
+			Ok(status) => status,
+			Err(e) => {
+				eprintln!("{e}");
+				e.status()
+			}
+		}
+	}))
+}
+
+fn read_upstream(upstream: Upstream) -> Result<Value, ExecutorError> {
+	let mut bytes = vec![];
+	match upstream {
+		Upstream::Terminal => {}
+		Upstream::Child(mut stdout) => {
+			stdout.read_to_end(&mut bytes)?;
+		}
+		Upstream::Pipe(mut reader) => {
+			reader.read_to_end(&mut bytes)?;
+		}
+		Upstream::Value(receiver) => {
+			// The sender is only dropped without sending if the previous stage failed.
+			return Ok(receiver.recv().unwrap_or(Value::String(String::new())));
+		}
+	}
+	Ok(Value::String(
+		String::from_utf8_lossy(&bytes).trim().to_string(),
+	))
+}
+
+/// Give a builtin's value to the next stage and return the stage's status. Like `run_value`, a
+/// status is the stage's own and neither it nor nil is printed.
+fn send_downstream(
+	value: Value,
+	downstream: Downstream,
+	context: &Context,
+) -> Result<Status, ExecutorError> {
+	let status = match value {
+		Value::Status(status) => status,
+		_ => Status::Exited(0),
+	};
+	match (downstream, value) {
+		(Downstream::Value(sender), value) => {
+			let _ = sender.send(value);
+		}
+		(_, Value::Status(_) | Value::Nil) => {}
+		(Downstream::Terminal, value) => println!("{}", value_to_string(value, context)?),
+		(Downstream::Pipe(mut writer), value) => {
+			let string = value_to_string(value, context)?;
+			// The next stage is free to exit without reading everything.
+			let _ = writeln!(writer, "{string}");
+		}
+	}
+	Ok(status)
+}
+
+/// Wait for every stage, even if waiting on an earlier one failed.
+fn wait_all(running: Vec<RunningStage>) -> Result<Vec<Status>, ExecutorError> {
+	let results: Vec<io::Result<Status>> = running
+		.into_iter()
+		.map(|stage| match stage {
+			RunningStage::Child(mut child) => child.wait().map(|status: ExitStatus| status.into()),
+			RunningStage::Thread(handle) => Ok(handle.join().unwrap_or(Status::Exited(1))),
+		})
+		.collect();
+	results.into_iter().map(|result| Ok(result?)).collect()
+}
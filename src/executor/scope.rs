@@ -0,0 +1,89 @@
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::executor::value::Value;
+
+/// The variables visible at one point, as a stack of scopes where lookup walks outward from the
+/// innermost one. Clones share the scopes, so a lambda that keeps a clone sees later changes to the
+/// variables it closed over, and can change them with `set!`.
+#[derive(Clone, Default)]
+pub struct Scope {
+	innermost: Arc<Frame>,
+}
+
+#[derive(Default)]
+struct Frame {
+	vars: Mutex<HashMap<String, Value>>,
+	parent: Option<Arc<Frame>>,
+}
+
+impl Scope {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Start a new innermost scope, for example for a function body.
+	pub fn push(&mut self) {
+		self.innermost = Arc::new(Frame {
+			vars: Mutex::default(),
+			parent: Some(self.innermost.clone()),
+		});
+	}
+
+	/// Leave the innermost scope, dropping its variables. The outermost scope is never left.
+	pub fn pop(&mut self) {
+		if let Some(parent) = &self.innermost.parent {
+			self.innermost = parent.clone();
+		}
+	}
+
+	pub fn get(&self, name: &str) -> Option<Value> {
+		self.frames()
+			.find_map(|frame| frame.lock().get(name).cloned())
+	}
+
+	pub fn contains_key(&self, name: &str) -> bool {
+		self.frames().any(|frame| frame.lock().contains_key(name))
+	}
+
+	/// Bind a variable in the innermost scope, shadowing any outer variable with the same name.
+	pub fn insert(&self, name: String, value: Value) {
+		self.innermost.lock().insert(name, value);
+	}
+
+	/// Change the closest existing variable with this name. Returns false if there is none.
+	pub fn set(&self, name: &str, value: Value) -> bool {
+		for frame in self.frames() {
+			if let Some(existing) = frame.lock().get_mut(name) {
+				*existing = value;
+				return true;
+			}
+		}
+		false
+	}
+
+	/// The names of every visible variable.
+	pub fn names(&self) -> Vec<String> {
+		let mut names: Vec<String> = self
+			.frames()
+			.flat_map(|frame| frame.lock().keys().cloned().collect::<Vec<String>>())
+			.collect();
+		names.sort_unstable();
+		names.dedup();
+		names
+	}
+
+	fn frames(&self) -> impl Iterator<Item = &Frame> {
+		std::iter::successors(Some(self.innermost.as_ref()), |frame| {
+			frame.parent.as_deref()
+		})
+	}
+}
+
+impl Frame {
+	fn lock(&self) -> MutexGuard<'_, HashMap<String, Value>> {
+		self.vars.lock().unwrap_or_else(|e| e.into_inner())
+	}
+}
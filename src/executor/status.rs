@@ -0,0 +1,62 @@
+use std::{fmt::Display, os::unix::process::ExitStatusExt, process::ExitStatus};
+
+/// How the last command finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+	Exited(i32),
+	Signalled(i32),
+	Stopped(i32),
+}
+
+impl Status {
+	pub fn success(&self) -> bool {
+		*self == Status::Exited(0)
+	}
+
+	/// The status as a single number, following the shell convention of 128 plus the signal
+	/// number for processes that were killed or stopped.
+	pub fn code(&self) -> i32 {
+		match self {
+			Status::Exited(code) => *code,
+			Status::Signalled(signal) | Status::Stopped(signal) => 128 + signal,
+		}
+	}
+
+	/// The status of a whole pipeline. Normally this is the status of the last stage, but with
+	/// `pipefail` it is the status of the last stage that failed.
+	pub fn of_pipeline(statuses: &[Status], pipefail: bool) -> Status {
+		let last_failure = statuses.iter().rev().find(|status| !status.success());
+		match (pipefail, last_failure, statuses.last()) {
+			(true, Some(failure), _) => *failure,
+			(_, _, Some(last)) => *last,
+			(_, _, None) => Status::default(),
+		}
+	}
+}
+
+impl Default for Status {
+	fn default() -> Self {
+		Status::Exited(0)
+	}
+}
+
+impl From<ExitStatus> for Status {
+	fn from(value: ExitStatus) -> Self {
+		match (value.code(), value.signal(), value.stopped_signal()) {
+			(Some(code), _, _) => Status::Exited(code),
+			(None, Some(signal), _) => Status::Signalled(signal),
+			(None, None, Some(signal)) => Status::Stopped(signal),
+			(None, None, None) => Status::Exited(1),
+		}
+	}
+}
+
+impl Display for Status {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Status::Exited(code) => write!(f, "{code}"),
+			Status::Signalled(signal) => write!(f, "killed by signal {signal}"),
+			Status::Stopped(signal) => write!(f, "stopped by signal {signal}"),
+		}
+	}
+}
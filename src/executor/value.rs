@@ -0,0 +1,78 @@
+use std::fmt::Display;
+
+use crate::executor::{function::Function, pipeline::Pipeline, status::Status};
+
+#[derive(Clone)]
+pub enum Value {
+	Command(Pipeline),
+	String(String),
+	Int(i64),
+	Float(f64),
+	Bool(bool),
+	/// No value, like the result of an `if` without an else branch.
+	Nil,
+	Record(Record),
+	List(Vec<Value>),
+	Lambda(Function),
+	/// The result of waiting for a job, which sets `$?` instead of being printed.
+	Status(Status),
+}
+
+impl Display for Value {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Value::Command(pipeline) => write!(f, "{pipeline}"),
+			Value::String(string) => write!(f, "{string}"),
+			Value::Int(int) => write!(f, "{int}"),
+			Value::Float(float) => write!(f, "{float}"),
+			Value::Bool(bool) => write!(f, "{bool}"),
+			Value::Nil => Ok(()),
+			Value::Record(record) => write!(f, "{record}"),
+			Value::List(items) => {
+				let lines: Vec<String> = items.iter().map(Value::to_string).collect();
+				write!(f, "{}", lines.join("\n"))
+			}
+			Value::Lambda(lambda) => write!(f, "{lambda}"),
+			Value::Status(status) => write!(f, "{}", status.code()),
+		}
+	}
+}
+
+/// A set of named fields, kept in the order they were inserted.
+#[derive(Clone, Default)]
+pub struct Record {
+	fields: Vec<(String, Value)>,
+}
+
+impl Record {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set a field, replacing the value if the field already exists.
+	pub fn insert(&mut self, key: String, value: Value) {
+		match self.fields.iter_mut().find(|(k, _)| *k == key) {
+			Some((_, existing)) => *existing = value,
+			None => self.fields.push((key, value)),
+		}
+	}
+
+	pub fn get(&self, key: &str) -> Option<&Value> {
+		self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &(String, Value)> {
+		self.fields.iter()
+	}
+}
+
+impl Display for Record {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let lines: Vec<String> = self
+			.fields
+			.iter()
+			.map(|(key, value)| format!("{key}: {value}"))
+			.collect();
+		write!(f, "{}", lines.join("\n"))
+	}
+}
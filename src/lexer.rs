@@ -8,6 +8,8 @@ pub enum Token {
 	FunctionEnd,
 	String(String),
 	Variable(String),
+	/// `...` before a variable or subcommand, whose items become separate arguments.
+	Spread,
 }
 
 /// Lex a string of lishp into a vector of tokens.
@@ -15,7 +17,7 @@ pub enum Token {
 /// ```
 /// use lishp::lexer::{lex, Token};
 ///
-/// let lexed = lex("ls (echo src)".to_string()).unwrap();
+/// let lexed = lex("ls (echo src)").unwrap();
 /// assert_eq!(
 /// 	lexed,
 /// 	vec![
@@ -32,11 +34,7 @@ pub fn lex(line: &str) -> Result<Vec<Token>, LexerError> {
 
 	let mut tokens = vec![];
 
-	loop {
-		let char = match chars.next() {
-			Some(res) => res,
-			None => break,
-		};
+	while let Some(char) = chars.next() {
 		match char {
 			'(' => tokens.push(Token::FunctionStart),
 			')' => tokens.push(Token::FunctionEnd),
@@ -68,16 +66,18 @@ pub fn lex(line: &str) -> Result<Vec<Token>, LexerError> {
 
 fn handle_argument(chars: &mut Chars<'_>, char: char) -> Result<Vec<Token>, LexerError> {
 	let mut arg = char.to_string();
-	loop {
-		let next_char = match chars.next() {
-			Some(res) => res,
-			// Just exit normally and let the outer function exit cleanly.
-			None => break,
-		};
+	// Running out of characters just exits normally and lets the outer function exit cleanly.
+	while let Some(next_char) = chars.next() {
 		match next_char {
+			'(' if arg == "..." => return Ok(vec![Token::Spread, Token::FunctionStart]),
 			'(' => return Err(LexerError::OpenParethesisInArg),
 			')' => return Ok(vec![Token::String(arg), Token::FunctionEnd]),
 			'"' => return Err(LexerError::InvalidCharInArg('"')),
+			'$' if arg == "..." => {
+				let mut tokens = vec![Token::Spread];
+				tokens.append(&mut handle_var(chars)?);
+				return Ok(tokens);
+			}
 			'$' => return Err(LexerError::InvalidCharInArg('$')),
 			'\\' => {
 				let next_char = match chars.next() {
@@ -96,12 +96,8 @@ fn handle_argument(chars: &mut Chars<'_>, char: char) -> Result<Vec<Token>, Lexe
 /// Handles a variable
 fn handle_var(chars: &mut Chars<'_>) -> Result<Vec<Token>, LexerError> {
 	let mut var = "".to_string();
-	loop {
-		let next_char = match chars.next() {
-			Some(res) => res,
-			// Just exit normally and let the outer function exit cleanly.
-			None => break,
-		};
+	// Running out of characters just exits normally and lets the outer function exit cleanly.
+	for next_char in chars.by_ref() {
 		match next_char {
 			'(' => return Err(LexerError::InvalidCharInVar('(')),
 			')' => return Ok(vec![Token::Variable(var), Token::FunctionEnd]),
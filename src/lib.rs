@@ -1,4 +1,5 @@
-pub const KEYWORDS: [&str; 5] = ["if", "pipe", "cd", "set-env", "get-env"];
+// Indentation is tabs everywhere, as set in rustfmt.toml, including doc comment examples.
+#![allow(clippy::tabs_in_doc_comments)]
 
 pub mod errors;
 pub mod executor;
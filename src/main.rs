@@ -1,7 +1,8 @@
 use std::{
 	env,
-	fs::{self, DirEntry},
-	io::Error,
+	fs::{self, DirEntry, File},
+	io::{self, BufRead, BufReader, Error, IsTerminal},
+	process,
 };
 
 use nu_ansi_term::{Color, Style};
@@ -11,7 +12,7 @@ use reedline::{
 };
 
 use lishp::{
-	executor::{context::Context, execute},
+	executor::{context::Context, execute, jobs::init_job_control, status::Status},
 	lexer::lex,
 	parser::parse,
 };
@@ -30,17 +31,44 @@ fn main() {
 		}
 	};
 
+	match env::args().nth(1) {
+		Some(path) => {
+			let file = match File::open(&path) {
+				Ok(res) => res,
+				Err(e) => {
+					eprintln!("Error opening {path}: {e}");
+					process::exit(1);
+				}
+			};
+			run_lines(BufReader::new(file), &mut context);
+		}
+		None if !io::stdin().is_terminal() => run_lines(io::stdin().lock(), &mut context),
+		None => {
+			run_interactive(&mut context);
+			return;
+		}
+	}
+	process::exit(context.last_status.code());
+}
+
+fn run_interactive(context: &mut Context) {
+	init_job_control();
 	let executables = executables_in_path();
 	let mut line_editor = get_line_editor();
 
 	loop {
-		let prompt = LishpPrompt::new(&context);
+		for line in context.jobs.reap() {
+			eprintln!("{line}");
+		}
+		let prompt = LishpPrompt::new(context);
 		//TODO Fork reedline and remove the clones.
 		let completer = Box::new(LishpCompleter::new(context.clone(), executables.clone()));
 		line_editor = line_editor.with_completer(completer);
 		let line = match line_editor.read_line(&prompt) {
 			Ok(Signal::Success(line)) => line,
-			Ok(Signal::CtrlC) | Ok(Signal::CtrlD) => {
+			// Reedline clears the line on Ctrl-C, the shell keeps running.
+			Ok(Signal::CtrlC) => continue,
+			Ok(Signal::CtrlD) => {
 				println!("Exiting, have a nice day :)");
 				break;
 			}
@@ -49,25 +77,49 @@ fn main() {
 				continue;
 			}
 		};
+		run_line(&line, context);
+	}
+}
 
-		let tokens = match lex(&line) {
+/// Runs every line from a script or pipe, skipping blank lines.
+fn run_lines(reader: impl BufRead, context: &mut Context) {
+	for line_res in reader.lines() {
+		let line = match line_res {
 			Ok(res) => res,
 			Err(e) => {
-				eprintln!("{e}");
-				continue;
-			}
-		};
-		let parsed = match parse(tokens) {
-			Ok(res) => res,
-			Err(e) => {
-				eprintln!("{e}");
-				continue;
+				eprintln!("Error reading line: {e}");
+				context.last_status = Status::Exited(1);
+				return;
 			}
 		};
-		execute(parsed, &mut context);
+		if line.trim().is_empty() {
+			continue;
+		}
+		run_line(&line, context);
 	}
 }
 
+fn run_line(line: &str, context: &mut Context) {
+	let tokens = match lex(line) {
+		Ok(res) => res,
+		Err(e) => {
+			eprintln!("{e}");
+			// Like other shells, use 2 for syntax errors.
+			context.last_status = Status::Exited(2);
+			return;
+		}
+	};
+	let parsed = match parse(tokens) {
+		Ok(res) => res,
+		Err(e) => {
+			eprintln!("{e}");
+			context.last_status = Status::Exited(2);
+			return;
+		}
+	};
+	execute(parsed, context);
+}
+
 fn get_line_editor() -> Reedline {
 	let completion_menu = Box::new(ColumnarMenu::default().with_name("completion_menu"));
 
@@ -1,14 +1,16 @@
 use crate::{errors::ParserError, lexer::Token};
 use std::vec::IntoIter;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Expression {
 	String(String),
 	Variable(String),
 	Function(Box<Func>),
+	/// A list whose items are passed as separate arguments.
+	Spread(Box<Expression>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Func {
 	pub name: Expression,
 	pub arguments: Vec<Expression>,
@@ -16,15 +18,31 @@ pub struct Func {
 
 impl Func {
 	fn empty() -> Self {
-		return Func {
+		Func {
 			name: Expression::String(String::new()),
 			arguments: vec![],
-		};
+		}
 	}
 
 	pub fn is_empty(&self) -> bool {
 		self.name == Expression::String(String::new()) && self.arguments == vec![]
 	}
+
+	/// Whether the variable is used anywhere in the call, including nested calls and lambdas.
+	pub fn uses_variable(&self, var: &str) -> bool {
+		self.name.uses_variable(var) || self.arguments.iter().any(|arg| arg.uses_variable(var))
+	}
+}
+
+impl Expression {
+	pub fn uses_variable(&self, var: &str) -> bool {
+		match self {
+			Expression::String(_) => false,
+			Expression::Variable(name) => name == var,
+			Expression::Function(func) => func.uses_variable(var),
+			Expression::Spread(expr) => expr.uses_variable(var),
+		}
+	}
 }
 
 pub fn parse(tokens: Vec<Token>) -> Result<Func, ParserError> {
@@ -45,23 +63,15 @@ fn parse_function(tokens: &mut IntoIter<Token>) -> Result<Func, ParserError> {
 		None => return Err(ParserError::ExpectedFunctionNameGotEOF),
 	};
 	let fn_name = match token {
-		Token::FunctionStart => Expression::Function(Box::new(parse_function(tokens)?)),
 		Token::FunctionEnd => return Ok(Func::empty()),
-		Token::String(string) => Expression::String(string),
-		Token::Variable(var) => Expression::Variable(var),
+		token => parse_expression(token, tokens)?,
 	};
 	let mut args = vec![];
 
-	loop {
-		let token = match tokens.next() {
-			Some(res) => res,
-			None => break,
-		};
+	while let Some(token) = tokens.next() {
 		let arg = match token {
-			Token::FunctionStart => Expression::Function(Box::new(parse_function(tokens)?)),
 			Token::FunctionEnd => break,
-			Token::String(string) => Expression::String(string),
-			Token::Variable(var) => Expression::Variable(var),
+			token => parse_expression(token, tokens)?,
 		};
 		args.push(arg);
 	}
@@ -71,3 +81,19 @@ fn parse_function(tokens: &mut IntoIter<Token>) -> Result<Func, ParserError> {
 		arguments: args,
 	})
 }
+
+fn parse_expression(token: Token, tokens: &mut IntoIter<Token>) -> Result<Expression, ParserError> {
+	let expression = match token {
+		Token::FunctionStart => Expression::Function(Box::new(parse_function(tokens)?)),
+		Token::FunctionEnd => return Err(ParserError::EndOfFunctionWhileStillTokens),
+		Token::String(string) => Expression::String(string),
+		Token::Variable(var) => Expression::Variable(var),
+		Token::Spread => match tokens.next() {
+			Some(token @ (Token::FunctionStart | Token::Variable(_))) => {
+				Expression::Spread(Box::new(parse_expression(token, tokens)?))
+			}
+			_ => return Err(ParserError::NothingToSpread),
+		},
+	};
+	Ok(expression)
+}
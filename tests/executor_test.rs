@@ -0,0 +1,1051 @@
+use std::{
+	io::Write,
+	process::{Command, Output, Stdio},
+};
+
+use lishp::{
+	executor::{
+		context::Context,
+		execute,
+		jobs::{catch_interrupts, init_job_control, job_control_enabled},
+		pipeline::Pipeline,
+		status::Status,
+		value::Value,
+		BUILTINS,
+	},
+	lexer::lex,
+	parser::parse,
+};
+
+fn run(line: &str, context: &mut Context) {
+	let parsed = parse(lex(line).unwrap()).unwrap();
+	execute(parsed, context);
+}
+
+/// Run a script with the shell binary, for what it prints.
+fn run_shell(script: &str) -> Output {
+	let mut shell = Command::new(env!("CARGO_BIN_EXE_lishp"))
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+	shell
+		.stdin
+		.take()
+		.unwrap()
+		.write_all(script.as_bytes())
+		.unwrap();
+	shell.wait_with_output().unwrap()
+}
+
+#[test]
+fn successful_command_status() {
+	let mut context = Context::new().unwrap();
+	run("true", &mut context);
+	assert_eq!(context.last_status, Status::Exited(0));
+}
+
+#[test]
+fn failed_command_status() {
+	let mut context = Context::new().unwrap();
+	run("sh -c \"exit 3\"", &mut context);
+	assert_eq!(context.last_status, Status::Exited(3));
+}
+
+#[test]
+fn killed_command_status() {
+	let mut context = Context::new().unwrap();
+	run("sh -c \"kill -9 $$\"", &mut context);
+	assert_eq!(context.last_status, Status::Signalled(9));
+	assert_eq!(context.last_status.code(), 137);
+}
+
+/// Run a test again in a copy of the test binary with stdin from /dev/null, so signals it sends
+/// and dispositions it changes don't affect other tests. Returns true in the copy, which should
+/// run the test, and false once the copy passed.
+fn in_own_process(test: &str) -> bool {
+	if std::env::var_os("LISHP_TEST_OWN_PROCESS").is_some() {
+		return true;
+	}
+	let status = Command::new(std::env::current_exe().unwrap())
+		.args(["--exact", test])
+		.env("LISHP_TEST_OWN_PROCESS", "1")
+		.stdin(Stdio::null())
+		.stdout(Stdio::null())
+		.status()
+		.unwrap();
+	assert!(status.success());
+	false
+}
+
+#[test]
+fn job_control_without_terminal() {
+	if !in_own_process("job_control_without_terminal") {
+		return;
+	}
+	assert!(!init_job_control());
+	assert!(!job_control_enabled());
+	// A shell started with Ctrl-C ignored, e.g. under nohup, still gives it back to its children.
+	unsafe {
+		libc::signal(libc::SIGINT, libc::SIG_IGN);
+	}
+	let mut context = Context::new().unwrap();
+	run("sh -c \"kill -INT $$\"", &mut context);
+	assert_eq!(context.last_status, Status::Signalled(libc::SIGINT));
+}
+
+#[test]
+fn interrupt_stops_loop() {
+	if !in_own_process("interrupt_stops_loop") {
+		return;
+	}
+	catch_interrupts();
+	std::thread::spawn(|| {
+		std::thread::sleep(std::time::Duration::from_millis(100));
+		unsafe {
+			libc::kill(libc::getpid(), libc::SIGINT);
+		}
+	});
+	let mut context = Context::new().unwrap();
+	run("loop (let x 1)", &mut context);
+	assert_eq!(context.last_status, Status::Signalled(libc::SIGINT));
+}
+
+#[test]
+fn missing_command_status() {
+	let mut context = Context::new().unwrap();
+	run("this-command-does-not-exist", &mut context);
+	assert_eq!(context.last_status, Status::Exited(127));
+	run("| (this-command-does-not-exist) (cat)", &mut context);
+	assert_eq!(context.last_status, Status::Exited(127));
+}
+
+#[test]
+fn status_variable() {
+	let mut context = Context::new().unwrap();
+	run("false", &mut context);
+	run("let code $?", &mut context);
+	assert_eq!(context.vars.get("code").unwrap().to_string(), "1");
+	assert_eq!(context.last_status, Status::Exited(0));
+}
+
+#[test]
+fn status_builtin() {
+	let mut context = Context::new().unwrap();
+	run("sh -c \"kill -9 $$\"", &mut context);
+	run("let description (status)", &mut context);
+	assert_eq!(
+		context.vars.get("description").unwrap().to_string(),
+		"killed by signal 9"
+	);
+}
+
+#[test]
+fn failed_substitution_is_error() {
+	let mut context = Context::new().unwrap();
+	run("let output (sh -c \"echo partial; exit 2\")", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+	assert!(!context.vars.contains_key("output"));
+}
+
+#[test]
+fn failed_substitution_when_not_strict() {
+	let mut context = Context::new().unwrap();
+	run("set-option strict-substitution false", &mut context);
+	run("let output (sh -c \"echo partial; exit 2\")", &mut context);
+	assert_eq!(context.last_status, Status::Exited(0));
+	assert_eq!(context.vars.get("output").unwrap().to_string(), "partial");
+}
+
+#[test]
+fn status_used_as_value() {
+	let mut context = Context::new().unwrap();
+	for line in [
+		"let v (do (sh -c \"exit 3\") (echo no))",
+		"let v (do (true) (false))",
+		"let v (for x in (list 1) (sh -c \"exit 5\"))",
+		"let v (each (fn (x) (false)) (list 1))",
+	] {
+		run(line, &mut context);
+		assert_eq!(context.last_status, Status::Exited(1), "{line}");
+		assert!(!context.vars.contains_key("v"), "{line}");
+	}
+	run("let v (echo A (let q 1) Z)", &mut context);
+	assert_eq!(context.vars.get("v").unwrap().to_string(), "A  Z");
+	run("set-option strict-substitution false", &mut context);
+	run("let v (do (false) (echo b))", &mut context);
+	assert_eq!(context.vars.get("v").unwrap().to_string(), "");
+}
+
+#[test]
+fn capture_command() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let result (capture (sh -c \"echo out; echo err >&2; exit 3\"))",
+		&mut context,
+	);
+	let record = match context.vars.get("result") {
+		Some(Value::Record(record)) => record,
+		_ => panic!("capture should return a record"),
+	};
+	assert_eq!(record.get("stdout").unwrap().to_string(), "out\n");
+	assert_eq!(record.get("stderr").unwrap().to_string(), "err\n");
+	assert!(matches!(record.get("status"), Some(Value::Int(3))));
+	assert!(matches!(record.get("duration"), Some(Value::Float(_))));
+	assert_eq!(context.last_status, Status::Exited(0));
+}
+
+#[test]
+fn get_field_of_capture() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let result (complete (sh -c \"echo problem >&2\"))",
+		&mut context,
+	);
+	run("let error (get $result stderr)", &mut context);
+	assert_eq!(context.vars.get("error").unwrap().to_string(), "problem\n");
+	run("let missing (get $result nonexistent)", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn pipe_string_into_command() {
+	let mut context = Context::new().unwrap();
+	run("let out (pipe \"-n -e hello\" (cat))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "-n -e hello");
+}
+
+#[test]
+fn pipe_string_through_several_commands() {
+	let mut context = Context::new().unwrap();
+	run("let out (| \"hello\" (tr a-z A-Z) (rev))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "OLLEH");
+}
+
+#[test]
+fn with_input() {
+	let mut context = Context::new().unwrap();
+	run("let out (with-input \"abc\" (tr a-z A-Z))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "ABC");
+}
+
+#[test]
+fn cloned_pipeline_keeps_input() {
+	let mut pipeline = Pipeline::from(Command::new("cat"));
+	pipeline.set_input("abc".to_string());
+	let clone = pipeline.clone();
+	assert_eq!(clone.output().unwrap().stdout, b"abc\n");
+	assert_eq!(pipeline.output().unwrap().stdout, b"abc\n");
+}
+
+#[test]
+fn pipe_status_of_every_stage() {
+	let mut context = Context::new().unwrap();
+	run("| (sh -c \"exit 2\") (cat) (true)", &mut context);
+	assert_eq!(
+		context.pipe_status,
+		vec![Status::Exited(2), Status::Exited(0), Status::Exited(0)]
+	);
+	assert_eq!(context.last_status, Status::Exited(0));
+	run("let codes (pipe-status)", &mut context);
+	assert_eq!(context.vars.get("codes").unwrap().to_string(), "2 0 0");
+}
+
+#[test]
+fn pipefail() {
+	let mut context = Context::new().unwrap();
+	run("set-option pipefail true", &mut context);
+	run(
+		"| (sh -c \"exit 2\") (sh -c \"exit 3\") (true)",
+		&mut context,
+	);
+	assert_eq!(context.last_status, Status::Exited(3));
+	run("let out (| (false) (echo hi))", &mut context);
+	assert!(!context.vars.contains_key("out"));
+}
+
+#[test]
+fn pipe_command_into_builtin() {
+	let mut context = Context::new().unwrap();
+	run("set-env LISHP_PIPE_FIRST LISHP_PIPE_SECOND", &mut context);
+	run("set-env LISHP_PIPE_SECOND done", &mut context);
+	run(
+		"let out (| (echo LISHP_PIPE_FIRST) (get-env))",
+		&mut context,
+	);
+	assert_eq!(
+		context.vars.get("out").unwrap().to_string(),
+		"LISHP_PIPE_SECOND"
+	);
+	run(
+		"let out (| (echo LISHP_PIPE_FIRST) (get-env) (get-env) (tr a-z A-Z))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "DONE");
+}
+
+#[test]
+fn pipe_value_into_builtin() {
+	let mut context = Context::new().unwrap();
+	run("set-env LISHP_PIPE_VALUE found", &mut context);
+	run("let out (| LISHP_PIPE_VALUE (get-env $in))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "found");
+}
+
+#[test]
+fn nested_in_is_not_appended() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let out (| (echo hi) (upper (format \"{}!\" $in)))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "HI!");
+	run("let out (| (echo x) (if (= $in x) yes no))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "yes");
+	run(
+		"let out (| (seq 3) (map (fn (n) (* $n 2)) (lines $in)))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "2\n4\n6");
+}
+
+#[test]
+fn failing_builtin_stage() {
+	let mut context = Context::new().unwrap();
+	run("| (echo LISHP_PIPE_UNSET) (get-env) (cat)", &mut context);
+	assert_eq!(
+		context.pipe_status,
+		vec![Status::Exited(0), Status::Exited(1), Status::Exited(0)]
+	);
+}
+
+#[test]
+fn jobs_without_jobs() {
+	let mut context = Context::new().unwrap();
+	run("let out (jobs)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "");
+	run("fg", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+	run("wait", &mut context);
+	assert_eq!(context.last_status, Status::Exited(0));
+}
+
+#[test]
+fn kill_by_pid() {
+	let mut context = Context::new().unwrap();
+	let mut child = std::process::Command::new("sleep")
+		.arg("10")
+		.spawn()
+		.unwrap();
+	run(&format!("kill -KILL {}", child.id()), &mut context);
+	assert_eq!(context.last_status, Status::Exited(0));
+	assert_eq!(Status::from(child.wait().unwrap()), Status::Signalled(9));
+	run("kill -NOPE 1", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn stopped_process_with_blocked_builtin() {
+	let mut context = Context::new().unwrap();
+	// `upper` is stuck writing to the stopped process until the background subshell continues it.
+	run(
+		"| (seq 200000) (upper) (sh -c \"(sleep 0.5; kill -CONT $$) & kill -STOP $$\")",
+		&mut context,
+	);
+	assert_eq!(context.last_status, Status::Stopped(libc::SIGSTOP));
+	run("fg", &mut context);
+	assert!(!matches!(context.last_status, Status::Stopped(_)));
+	assert!(context.jobs.take(None).is_none());
+}
+
+#[test]
+fn stopped_status_code() {
+	assert_eq!(Status::Stopped(20).code(), 148);
+	assert!(!Status::Stopped(20).success());
+}
+
+#[test]
+fn defn_and_call() {
+	let mut context = Context::new().unwrap();
+	run(
+		"defn greet (greeting name) (echo $greeting $name)",
+		&mut context,
+	);
+	run("let out (greet hello world)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "hello world");
+	run("greet hello", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn defn_cannot_shadow_builtins() {
+	let mut context = Context::new().unwrap();
+	for (name, _) in BUILTINS {
+		run(&format!("defn \"{name}\" (x) (echo $x)"), &mut context);
+		assert_eq!(context.last_status, Status::Exited(1), "defn {name}");
+	}
+	assert!(context.functions.is_empty());
+}
+
+#[test]
+fn function_variables_are_local() {
+	let mut context = Context::new().unwrap();
+	run("defn inner (x) (let y $x) (echo $y)", &mut context);
+	run("let out (inner value)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "value");
+	assert!(!context.vars.contains_key("x"));
+	assert!(!context.vars.contains_key("y"));
+}
+
+#[test]
+fn builtin_status_at_end_of_pipeline() {
+	let mut context = Context::new().unwrap();
+	run(
+		"| (echo x) (each (fn (line) (sh -c \"exit 4\")) $in)",
+		&mut context,
+	);
+	assert_eq!(
+		context.pipe_status,
+		vec![Status::Exited(0), Status::Exited(4)]
+	);
+	let output = run_shell("| (echo x) (let y $in)\n| (echo x) (each (fn (line) (true)) $in)\n");
+	assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+}
+
+#[test]
+fn function_names_missing_command_it_returns() {
+	let output = run_shell(
+		"defn build () (echo start) (lishp-missing-command)\nbuild\ndefn outer () (build)\nouter\n",
+	);
+	assert_eq!(output.status.code(), Some(127));
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	let lines: Vec<&str> = stderr.lines().collect();
+	assert_eq!(lines.len(), 2, "{stderr}");
+	assert!(lines[0].starts_with("build: lishp-missing-command: "));
+	assert!(lines[1].starts_with("outer: build: lishp-missing-command: "));
+}
+
+#[test]
+fn function_as_pipeline_stage() {
+	let mut context = Context::new().unwrap();
+	run("defn shout (text) (| $text (tr a-z A-Z))", &mut context);
+	run("let out (| (echo quiet) (shout) (rev))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "TEIUQ");
+}
+
+#[test]
+fn map_with_lambda() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let out (map (fn (x) (echo item $x)) (list a b))",
+		&mut context,
+	);
+	assert!(matches!(context.vars.get("out"), Some(Value::List(items)) if items.len() == 2));
+	assert_eq!(
+		context.vars.get("out").unwrap().to_string(),
+		"item a\nitem b"
+	);
+}
+
+#[test]
+fn lambda_captures_variables() {
+	let mut context = Context::new().unwrap();
+	run(
+		"defn make-suffixer (suffix) (fn (x) (echo $x $suffix))",
+		&mut context,
+	);
+	run("let add-suffix (make-suffixer first)", &mut context);
+	run("let suffix second", &mut context);
+	run("let out (map $add-suffix (printf a))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "a first");
+	run("let out ($add-suffix b)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "b first");
+}
+
+#[test]
+fn filter_reduce_any_all() {
+	let mut context = Context::new().unwrap();
+	run("let not (fn (x) (if $x false true))", &mut context);
+	run(
+		"let out (filter $not (list true false false))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "false\nfalse");
+	run(
+		"let out (reduce (fn (acc x) (echo $acc $x)) start (list a b))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "start a b");
+	run("let out (any $not (list true false))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "true");
+	run("let out (all $not (list true false))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "false");
+}
+
+#[test]
+fn let_shadows_and_set_changes_outer_binding() {
+	let mut context = Context::new().unwrap();
+	run("let count outer", &mut context);
+	run(
+		"defn shadow () (let count inner) (echo $count)",
+		&mut context,
+	);
+	run("let out (shadow)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "inner");
+	assert_eq!(context.vars.get("count").unwrap().to_string(), "outer");
+	run("defn change () (set! count changed)", &mut context);
+	run("change", &mut context);
+	assert_eq!(context.vars.get("count").unwrap().to_string(), "changed");
+	run("set! missing value", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn lambda_sees_later_changes_to_captured_variables() {
+	let mut context = Context::new().unwrap();
+	run(
+		"defn make-counter (count) (fn () (set! count (echo $count x)) (echo $count))",
+		&mut context,
+	);
+	run("let counter (make-counter 0)", &mut context);
+	run("$counter", &mut context);
+	run("let out ($counter)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "0 x x");
+	assert!(!context.vars.contains_key("count"));
+}
+
+#[test]
+fn side_effects_inside_expressions() {
+	let mut context = Context::new().unwrap();
+	run("if true (cd /) (cd /tmp)", &mut context);
+	assert_eq!(context.working_dir, std::path::PathBuf::from("/"));
+	run("if false (let x yes) (let x no)", &mut context);
+	assert_eq!(context.vars.get("x").unwrap().to_string(), "no");
+}
+
+#[test]
+fn do_returns_last_value() {
+	let mut context = Context::new().unwrap();
+	run("let out (do (let x first) (echo $x second))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "first second");
+	assert!(!context.vars.contains_key("x"));
+	run("if true (do (cd /) (let y 1)) (echo never)", &mut context);
+	assert_eq!(context.working_dir, std::path::PathBuf::from("/"));
+}
+
+#[test]
+fn do_stops_at_first_failure() {
+	let mut context = Context::new().unwrap();
+	run("let reached no", &mut context);
+	run("do (false) (set! reached yes)", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+	assert_eq!(context.vars.get("reached").unwrap().to_string(), "no");
+	run(
+		"do (get-env LISHP_DO_UNSET) (set! reached yes)",
+		&mut context,
+	);
+	assert_eq!(context.last_status, Status::Exited(1));
+	assert_eq!(context.vars.get("reached").unwrap().to_string(), "no");
+}
+
+#[test]
+fn for_over_list_and_command_output() {
+	let mut context = Context::new().unwrap();
+	run("let seen start", &mut context);
+	run(
+		"for x in (list a b) (set! seen (echo $seen $x))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("seen").unwrap().to_string(), "start a b");
+	run(
+		"for line in (printf \"c\\\\nd\") (set! seen (echo $seen $line x))",
+		&mut context,
+	);
+	assert_eq!(
+		context.vars.get("seen").unwrap().to_string(),
+		"start a b c x d x"
+	);
+	assert!(!context.vars.contains_key("x"));
+	run("for x in (list a) (false)", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn while_loop_break_and_continue() {
+	let mut context = Context::new().unwrap();
+	run("let running true", &mut context);
+	run("let count none", &mut context);
+	run(
+		"while $running (set! count (echo $count x)) (set! running false)",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("count").unwrap().to_string(), "none x");
+	run(
+		"loop (set! count done) (break) (set! count never)",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("count").unwrap().to_string(), "done");
+	run(
+		"for x in (list true false) (if $x (continue) (set! count $x))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("count").unwrap().to_string(), "false");
+	run("break", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn arithmetic() {
+	let mut context = Context::new().unwrap();
+	run("let out (+ 1 2 (* 3 4))", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(15))));
+	run("let out (- 10 (/ 7 2))", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Float(f)) if f == 6.5));
+	run("let out (mod -7 3)", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(2))));
+	run(
+		"let out (max (pow 2 10) (abs -2000) (min 5 1.5))",
+		&mut context,
+	);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(2000))));
+}
+
+#[test]
+fn arithmetic_parses_command_output() {
+	let mut context = Context::new().unwrap();
+	run("let out (+ 1 (printf \" 41 \"))", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(42))));
+	run("let out (+ 1 (echo none))", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn arithmetic_errors() {
+	let mut context = Context::new().unwrap();
+	for line in [
+		"/ 1 0",
+		"mod 1 0",
+		"+ 9223372036854775807 1",
+		"* 9223372036854775807 2",
+		"pow 2 64",
+		"abs -9223372036854775808",
+	] {
+		context.last_status = Status::Exited(0);
+		run(line, &mut context);
+		assert_eq!(context.last_status, Status::Exited(1), "{line}");
+	}
+}
+
+#[test]
+fn comparisons() {
+	let mut context = Context::new().unwrap();
+	for (line, expected) in [
+		("= 1 1.0", true),
+		("= a a b", false),
+		("!= a b", true),
+		("< 9 10", true),
+		("< 1 2 2", false),
+		("<= 1 2 2", true),
+		("> b a", true),
+		(">= (printf 10) 9", true),
+	] {
+		run(&format!("let out ({line})"), &mut context);
+		assert!(
+			matches!(context.vars.get("out"), Some(Value::Bool(b)) if b == expected),
+			"{line}"
+		);
+	}
+}
+
+#[test]
+fn and_or_short_circuit() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let out (and false (this-command-does-not-exist))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "false");
+	run(
+		"let out (or (= 1 1) (this-command-does-not-exist))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "true");
+	run("let out (not (and true (< 2 1)))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "true");
+	run("let out (if (> 2 1) bigger smaller)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "bigger");
+}
+
+#[test]
+fn command_as_if_predicate() {
+	let mut context = Context::new().unwrap();
+	run("let out (if (true) yes no)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "yes");
+	run("let out (if (sh -c \"exit 2\") yes no)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "no");
+	run("let out (if (test -d /) (echo is dir))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "is dir");
+}
+
+#[test]
+fn if_without_else_is_nil() {
+	let mut context = Context::new().unwrap();
+	run("let out (if false never)", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Nil)));
+	run("if (false) (echo never)", &mut context);
+	assert_eq!(context.last_status, Status::Exited(0));
+	run("let out (if $out yes no)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "no");
+}
+
+#[test]
+fn cond_picks_first_true_branch() {
+	let mut context = Context::new().unwrap();
+	run("let n 5", &mut context);
+	run(
+		"let out (cond (< $n 3) small (test -d /) medium _ large)",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "medium");
+	run("let out (cond false never)", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Nil)));
+}
+
+#[test]
+fn match_patterns() {
+	let mut context = Context::new().unwrap();
+	for (value, expected) in [
+		("Cargo.toml", "literal"),
+		("main.rs", "glob"),
+		("v1.2", "regex"),
+		("42", "lambda"),
+		("other", "default"),
+	] {
+		run(
+			&format!(
+				"let out (match {value} Cargo.toml literal *.rs glob \"/^v[0-9]+[.][0-9]+$/\" regex (fn (x) (= $x 42.0)) lambda _ default)"
+			),
+			&mut context,
+		);
+		assert_eq!(context.vars.get("out").unwrap().to_string(), expected);
+	}
+}
+
+#[test]
+fn match_runs_subject_once() {
+	let mut context = Context::new().unwrap();
+	let log = std::env::temp_dir().join(format!("lishp-match-{}", std::process::id()));
+	let _ = std::fs::remove_file(&log);
+	let log = log.display();
+	run(
+		&format!("let out (match (sh -c \"echo run >> {log}; echo abc\") (fn (x) (= $x b)) one (fn (x) (= $x c)) two abc three _ none)"),
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "three");
+	assert_eq!(std::fs::read_to_string(log.to_string()).unwrap(), "run\n");
+	std::fs::remove_file(log.to_string()).unwrap();
+	run(
+		"let out (match (with-input abc (cat)) abc yes _ no)",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "yes");
+}
+
+#[test]
+fn string_builtins() {
+	let mut context = Context::new().unwrap();
+	for (line, expected) in [
+		("join - (split , a,b,c)", "a-b-c"),
+		("join , (split \"a  b\")", "a,b"),
+		("trim \"  hi  \"", "hi"),
+		("replace l L hello", "heLLo"),
+		("upper straße", "STRASSE"),
+		("lower ÉCOLE", "école"),
+		("substr 1 3 héllo", "éll"),
+		("substr -2 héllo", "lo"),
+		("pad 4 ab", "ab  "),
+		("pad -4 0 42", "0042"),
+		("starts-with? he hello", "true"),
+		("ends-with? he hello", "false"),
+		("contains? ll hello", "true"),
+		("| (echo quiet) (upper)", "QUIET"),
+	] {
+		run(&format!("let out ({line})"), &mut context);
+		assert_eq!(
+			context.vars.get("out").unwrap().to_string(),
+			expected,
+			"{line}"
+		);
+	}
+}
+
+#[test]
+fn caret_runs_command_instead_of_builtin() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let out (| (sh -c \"echo a; echo a\") (^uniq -c))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "2 a");
+	run("defn shadowed () (echo function)", &mut context);
+	run("^shadowed", &mut context);
+	assert_eq!(context.last_status, Status::Exited(127));
+}
+
+#[test]
+fn builtins_reject_command_flags() {
+	let mut context = Context::new().unwrap();
+	for line in [
+		"split -l",
+		"join -t a",
+		"uniq -c",
+		"first -n",
+		"last --lines",
+		"length -w",
+		"sort-by -n (list)",
+	] {
+		run(line, &mut context);
+		assert_eq!(context.last_status, Status::Exited(1), "{line}");
+	}
+	run("let out (split -- a--b)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "a\nb");
+}
+
+#[test]
+fn len_counts_characters() {
+	let mut context = Context::new().unwrap();
+	run("let out (len héllo👍🏽)", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(6))));
+	run("let out (len (split \"\" e\u{301}a))", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(2))));
+	run("len a b", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn regex_builtins() {
+	let mut context = Context::new().unwrap();
+	for (line, expected) in [
+		("re-match? \"^[0-9]+$\" 123", "true"),
+		("re-match? \"^[0-9]+$\" 12a", "false"),
+		("re-find \"[0-9]+\" abc123def45", "123"),
+		("re-find \"([a-z]+)=([0-9]+)\" x=1", "x\n1"),
+		(
+			"re-find \"(?P<key>[a-z]+)=(?P<value>[0-9]+)\" x=1",
+			"key: x\nvalue: 1",
+		),
+		("re-find-all \"[0-9]+\" a1b22c333", "1\n22\n333"),
+		("re-replace \"([a-z])([0-9])\" \"\\$2\\$1\" a1b2", "1a2b"),
+		("join - (re-split \"[,;] *\" \"a, b;c\")", "a-b-c"),
+	] {
+		run(&format!("let out ({line})"), &mut context);
+		assert_eq!(
+			context.vars.get("out").unwrap().to_string(),
+			expected,
+			"{line}"
+		);
+	}
+	run("let out (re-find x abc)", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Nil)));
+	run("re-find \"[a-\" abc", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn format_fills_placeholders() {
+	let mut context = Context::new().unwrap();
+	run("let name world", &mut context);
+	for (line, expected) in [
+		("format \"hello {name}\"", "hello world"),
+		("format \"{} + {} = {}\" 1 2 (+ 1 2)", "1 + 2 = 3"),
+		("format \"{1} {0}\" a b", "b a"),
+		("format \"{:.2}\" 3.14159", "3.14"),
+		("format \"{:>5}|{:<4}|{:^5}\" ab cd ef", "   ab|cd  | ef  "),
+		("format \"{:*^7}\" mid", "**mid**"),
+		("format \"{:05}\" -42", "-0042"),
+		("format \"{:+}\" 3", "+3"),
+		("format \"{:x} {:b}\" 255 5", "ff 101"),
+		("format \"{:.3}\" abcdef", "abc"),
+		("format \"{{{}}}\" x", "{x}"),
+	] {
+		run(&format!("let out ({line})"), &mut context);
+		assert_eq!(
+			context.vars.get("out").unwrap().to_string(),
+			expected,
+			"{line}"
+		);
+	}
+	for line in [
+		"format \"{} {}\" a",
+		"format {} a b",
+		"format \"{:q}\" a",
+		"format \"{:x}\" 1.5",
+		"format \"{\" a",
+	] {
+		context.last_status = Status::Exited(0);
+		run(line, &mut context);
+		assert_eq!(context.last_status, Status::Exited(1), "{line}");
+	}
+}
+
+#[test]
+fn lines_words_and_raw_output() {
+	let mut context = Context::new().unwrap();
+	run("let out (len (lines (seq 3)))", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(3))));
+	run("let out (words \" a  b\tc \")", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "a\nb\nc");
+	run("let out (raw (echo \" padded \"))", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), " padded \n");
+}
+
+#[test]
+fn spread_list_into_arguments() {
+	let mut context = Context::new().unwrap();
+	run("let xs (list a b c)", &mut context);
+	run("let out (echo ...$xs end)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "a b c end");
+	run("let out (+ ...(seq 4))", &mut context);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(10))));
+	run("let cmd (list echo hi)", &mut context);
+	run("let out (...$cmd there)", &mut context);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "hi there");
+}
+
+#[test]
+fn json_round_trip() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let data (from-json \"{\\\"name\\\": \\\"lishp\\\", \\\"tags\\\": [1, 2.5, true, null]}\")",
+		&mut context,
+	);
+	let record = match context.vars.get("data") {
+		Some(Value::Record(record)) => record,
+		_ => panic!("expected a record"),
+	};
+	assert_eq!(record.get("name").unwrap().to_string(), "lishp");
+	assert!(
+		matches!(record.get("tags"), Some(Value::List(items)) if matches!(
+			items.as_slice(),
+			[Value::Int(1), Value::Float(_), Value::Bool(true), Value::Nil]
+		))
+	);
+	run("let out (to-json --compact $data)", &mut context);
+	assert_eq!(
+		context.vars.get("out").unwrap().to_string(),
+		r#"{"name":"lishp","tags":[1,2.5,true,null]}"#
+	);
+	run("let out (to-json (list a))", &mut context);
+	assert_eq!(
+		context.vars.get("out").unwrap().to_string(),
+		"[\n  \"a\"\n]"
+	);
+	run("from-json \"{nope\"", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn csv_and_toml() {
+	let mut context = Context::new().unwrap();
+	run("let rows (from-csv \"name,age\na,1\nb,2\")", &mut context);
+	let rows = match context.vars.get("rows") {
+		Some(Value::List(rows)) => rows,
+		_ => panic!("expected a list"),
+	};
+	assert_eq!(rows.len(), 2);
+	assert!(matches!(&rows[1], Value::Record(row) if row.get("age").unwrap().to_string() == "2"));
+	run("let out (to-csv --tsv $rows)", &mut context);
+	assert_eq!(
+		context.vars.get("out").unwrap().to_string(),
+		"name\tage\na\t1\nb\t2\n"
+	);
+	run(
+		"let out (to-csv --delimiter ; (from-csv --no-header \"x,\\\"y z\\\"\"))",
+		&mut context,
+	);
+	assert_eq!(context.vars.get("out").unwrap().to_string(), "x;y z\n");
+
+	run(
+		"let doc (from-toml \"[package]\nname = \\\"lishp\\\"\nedition = 2021\")",
+		&mut context,
+	);
+	run("let out (to-toml $doc)", &mut context);
+	assert_eq!(
+		context.vars.get("out").unwrap().to_string(),
+		"[package]\nname = \"lishp\"\nedition = 2021\n"
+	);
+	run("to-toml (list 1 2)", &mut context);
+	assert_eq!(context.last_status, Status::Exited(1));
+}
+
+#[test]
+fn table_commands() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let people (from-json \"[{\\\"name\\\": \\\"b\\\", \\\"age\\\": 40, \\\"team\\\": \\\"x\\\"}, {\\\"name\\\": \\\"a\\\", \\\"age\\\": 9, \\\"team\\\": \\\"y\\\"}, {\\\"name\\\": \\\"c\\\", \\\"age\\\": 30, \\\"team\\\": \\\"x\\\"}]\")",
+		&mut context,
+	);
+	for (line, expected) in [
+		(
+			"to-json --compact (select name (where (fn (p) (> (get $p age) 20)) $people))",
+			r#"[{"name":"b"},{"name":"c"}]"#,
+		),
+		(
+			"to-json --compact (reject age team (first $people))",
+			r#"{"name":"b"}"#,
+		),
+		(
+			"join , (map (fn (p) (get $p name)) (sort-by age $people))",
+			"a,c,b",
+		),
+		(
+			"join , (map (fn (p) (get $p name)) (sort-by --reverse name $people))",
+			"c,b,a",
+		),
+		("length (get (group-by team $people) x)", "2"),
+		("join , (uniq (list b a b))", "b,a"),
+		(
+			"join , (uniq (list 1.1 1.10 007 7 1e2 100))",
+			"1.1,1.10,007,7,1e2,100",
+		),
+		("length (uniq (list (+ 0 1) (+ 0.5 0.5) 1))", "2"),
+		("join , (last 2 (list 1 2 3))", "2,3"),
+		("length $people", "3"),
+	] {
+		run(&format!("let out ({line})"), &mut context);
+		assert_eq!(
+			context.vars.get("out").unwrap().to_string(),
+			expected,
+			"{line}"
+		);
+	}
+}
+
+#[test]
+fn sort_by_mixed_keys() {
+	let mut context = Context::new().unwrap();
+	run(
+		"let out (join , (sort-by (fn (x) $x) (list 10 1a 9 b 2.5 1z)))",
+		&mut context,
+	);
+	assert_eq!(
+		context.vars.get("out").unwrap().to_string(),
+		"2.5,9,10,1a,1z,b"
+	);
+	// Enough items for the sort to notice an order that isn't total.
+	let items: Vec<String> = (0..300)
+		.map(|i| match i * 40503 % 65536 {
+			x if x % 4 == 0 => format!("{}a", x % 50),
+			x => (x % 200).to_string(),
+		})
+		.collect();
+	run(
+		&format!(
+			"let out (length (sort-by (fn (x) $x) (list {})))",
+			items.join(" ")
+		),
+		&mut context,
+	);
+	assert!(matches!(context.vars.get("out"), Some(Value::Int(300))));
+}
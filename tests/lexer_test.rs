@@ -123,3 +123,20 @@ fn unclosed_quote_error() {
 	let lexed = lex("ls \"src");
 	assert_eq!(lexed, Err(LexerError::UnclosedQuote));
 }
+
+#[test]
+fn spread() {
+	let lexed = lex("echo ...$files ...(ls)").unwrap();
+	assert_eq!(
+		lexed,
+		vec![
+			Token::String("echo".to_string()),
+			Token::Spread,
+			Token::Variable("files".to_string()),
+			Token::Spread,
+			Token::FunctionStart,
+			Token::String("ls".to_string()),
+			Token::FunctionEnd,
+		]
+	);
+}